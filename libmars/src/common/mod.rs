@@ -91,6 +91,30 @@ impl Dimensions {
     pub fn as_tuple(&self) -> (i32, i32, u32, u32) {
         (self.x, self.y, self.w, self.h)
     }
+
+    /// Position at which the dimensions stick to nearby edges
+    ///
+    /// * `areas` - Areas whose inner edges the dimensions should stick to (like monitors)
+    /// * `windows` - Other windows whose outer edges the dimensions should stick to
+    /// * `threshold` - Maximum distance in pixels to an edge
+    pub fn snap_position(&self, areas: &[Dimensions], windows: &[Dimensions], threshold: u32) -> (i32, i32) {
+        let overlaps_vertically = |d: &&Dimensions| d.y() < self.bottom() && self.y() < d.bottom();
+        let overlaps_horizontally = |d: &&Dimensions| d.x() < self.right() && self.x() < d.right();
+
+        let x_candidates = areas.iter().flat_map(|a| [a.x(), a.right() - self.w() as i32])
+            .chain(windows.iter().filter(overlaps_vertically)
+                   .flat_map(|d| [d.right(), d.x() - self.w() as i32]));
+        let y_candidates = areas.iter().flat_map(|a| [a.y(), a.bottom() - self.h() as i32])
+            .chain(windows.iter().filter(overlaps_horizontally)
+                   .flat_map(|d| [d.bottom(), d.y() - self.h() as i32]));
+
+        let snap = |pos: i32, candidates: &mut dyn Iterator<Item = i32>| {
+            candidates.filter(|c| c.abs_diff(pos) <= threshold)
+                .min_by_key(|c| c.abs_diff(pos))
+                .unwrap_or(pos)
+        };
+        (snap(self.x(), &mut x_candidates.into_iter()), snap(self.y(), &mut y_candidates.into_iter()))
+    }
 }
 
 impl MonitorConfig {
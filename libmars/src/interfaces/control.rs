@@ -16,7 +16,10 @@ pub trait WMController<W> {
     fn fullscreen_window(&self, window: W, mode: SettingMode) -> Result<()>;
     fn get_active_window(&self) -> Result<W>;
     fn get_workspace(&self, window: W) -> Result<u32>;
+    fn insert_workspace(&self, workspace: u32) -> Result<()>;
     fn pin_window(&self, window: W, mode: SettingMode) -> Result<()>;
+    fn remove_workspace(&self, workspace: u32) -> Result<()>;
+    fn rename_workspace(&self, workspace: u32, name: String) -> Result<()>;
     fn send_window_to_workspace(&self, window: W, workspace: u32) -> Result<()>;
     fn set_status(&self, status: String) -> Result<()>;
     fn switch_workspace(&self, workspace: u32) -> Result<()>;
@@ -2,6 +2,7 @@
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 
 use crate::common::*;
 use crate::common::error::*;
@@ -17,12 +18,30 @@ pub trait WindowManager<B: Backend<A>, A> {
     fn fullscreen_client(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, state: bool);
     fn handle_button(&mut self, backend: &mut B, modifiers: u32, button: u32, target: ButtonTarget,
                      client_option: Option<Rc<RefCell<B::Client>>>);
+    /// Called when the title or the hints of a client change
+    fn handle_client_update(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>);
     fn handle_key(&mut self, backend: &mut B, modifiers: u32, key: u32, client_option: Option<Rc<RefCell<B::Client>>>);
+    /// Handle a released key (`modifiers` describes the state after the release)
+    fn handle_key_release(&mut self, backend: &mut B, modifiers: u32, key: u32);
+    /// Handle the expiration of a timeout requested with [Backend::set_timeout]
+    fn handle_timeout(&mut self, backend: &mut B);
+    /// Called when one of the buttons in the title bar of a client is clicked
+    fn handle_title_button(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, index: usize, button: u32);
+    /// Insert a new workspace at the given index (indices after the last workspace append a workspace)
+    fn insert_workspace(&mut self, backend: &mut B, workspace_idx: u32);
     fn manage(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, workspace_preference: Option<u32>);
     fn move_request(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, x: i32, y: i32) -> bool;
     fn move_to_workspace(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, workspace_idx: u32);
+    /// Reload the colors of the theme
+    fn reload_theme(&mut self, backend: &mut B);
+    /// Remove a workspace and move its clients to a neighboring workspace
+    fn remove_workspace(&mut self, backend: &mut B, workspace_idx: u32);
+    /// Rename workspaces to the given names (in order of their indices)
+    fn rename_workspaces(&mut self, backend: &mut B, names: Vec<String>);
     fn resize_request(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, width: u32, height: u32) -> bool;
     fn set_client_pinned(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, state: bool);
+    /// Add or remove workspaces at the end until there are `count` workspaces
+    fn set_workspace_count(&mut self, backend: &mut B, count: u32);
     fn switch_workspace(&mut self, backend: &mut B, workspace_idx: u32);
     fn tile_client(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, state: bool);
     fn toggle_fullscreen_client(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>);
@@ -32,7 +51,10 @@ pub trait WindowManager<B: Backend<A>, A> {
 }
 
 pub trait Client<A>: Eq + Dimensioned {
-    fn add_title(&mut self, font: &str, hpad: u32, vpad: u32, color: u64) -> Result<()>;
+    fn add_title(&mut self, font: &str, hpad: u32, vpad: u32, color: u64, alignment: TitleAlignment) -> Result<()>;
+    /// Add buttons with the given labels to the right end of the title bar
+    fn add_title_buttons(&mut self, labels: &[String], font: &str, hpad: u32, vpad: u32, color: u64,
+                         hover_color: u64) -> Result<()>;
     fn application(&self) -> String;
     fn attributes(&self) -> &A;
     fn attributes_mut(&mut self) -> &mut A;
@@ -46,27 +68,40 @@ pub trait Client<A>: Eq + Dimensioned {
     fn export_workspace(&self, workspace_idx: u32);
     fn frame_width(&self) -> (u32, u32, u32, u32);
     fn hide(&mut self);
+    /// Identifier of the client window on the platform
+    fn id(&self) -> u64;
     fn inner_bw(&self) -> u32;
     // dimensions excluding all borders
     fn inner_dimensions(&self) -> Dimensions;
+    /// Instance part of the class hint (the class itself is available through [Client::application])
+    fn instance(&self) -> String;
     fn is_centered_on_screen(&self, dimensions: Dimensions) -> bool;
     fn is_dialog(&self) -> bool;
     fn is_fullscreen(&self) -> bool;
+    /// Whether the client demands the attention of the user
+    fn is_urgent(&self) -> bool;
     fn is_visible(&self) -> bool;
     fn move_resize(&mut self, x: i32, y: i32, width: u32, height: u32);
     fn name(&self) -> &str;
     fn outer_bw(&self) -> u32;
     fn raise(&self);
+    /// Round the corners of the frame (0 to disable)
+    fn set_corner_radius(&mut self, radius: u32);
     fn set_dimensions(&mut self, dimensions: Dimensions);
     fn set_frame_color(&mut self, color: u64);
     fn set_frame_width(&mut self, width: (u32, u32, u32, u32));
+    /// Temporarily remove frame and borders (they are restored with `false`)
+    fn set_decoration_hidden(&mut self, state: bool);
     fn set_fullscreen(&mut self, monitor_conf: &MonitorConfig);
     fn set_height(&mut self, height: u32);
     fn set_inner_bw(&mut self, bw: u32);
     fn set_inner_color(&mut self, color: u64);
     fn set_outer_bw(&mut self, bw: u32);
     fn set_outer_color(&mut self, color: u64);
+    fn set_title_button_hover_color(&mut self, color: u64);
     fn set_title_color(&mut self, color: u64);
+    /// Text shown in the title bar (defaults to the title of the window, unchanged labels are ignored)
+    fn set_title_label(&mut self, label: String);
     fn show(&mut self);
     fn title(&self) -> String;
     fn total_bw(&self) -> (u32, u32, u32, u32);
@@ -90,12 +125,24 @@ pub trait Backend<A> {
     /// Make currently active workspace available to clients
     fn export_current_workspace(&self, workspace_idx: u32);
 
+    /// Make the keys of a partially entered key sequence available to clients (empty if there is none)
+    fn export_key_prefix(&self, prefix: &str);
+
+    /// Make the active binding mode available to clients (empty for the default mode)
+    fn export_mode(&self, mode: &str);
+
+    /// Tell clients like status bars that the theme has changed
+    fn export_theme_change(&self);
+
     /// Make information about workspaces available to clients
     fn export_workspaces(&self, workspaces: Vec<(String, Dimensions, Dimensions)>);
 
     /// Get monitor configuration
     fn get_monitor_config(&self) -> Vec<MonitorConfig>;
 
+    /// Grab the keyboard so all key events are reported to the window manager
+    fn grab_keyboard(&self) -> bool;
+
     /// Handle windows existing before initialization
     fn handle_existing_windows(&mut self, wm: &mut dyn WindowManager<Self, A>);
 
@@ -121,6 +168,13 @@ pub trait Backend<A> {
     /// Set client that receives mouse and keyboard inputs
     fn set_input_focus(&self, client_rc: Rc<RefCell<Self::Client>>);
 
+    /// Request a call to [WindowManager::handle_timeout] after the given duration
+    /// (replaces a previously requested timeout, `None` cancels it)
+    fn set_timeout(&mut self, timeout: Option<Duration>);
+
+    /// Release a keyboard grab
+    fn ungrab_keyboard(&self);
+
     fn warp_pointer(&self, x: i32, y: i32);
 
     fn shutdown(&mut self);
@@ -134,4 +188,15 @@ pub enum ButtonTarget {
     Window,
     Root,
     Frame,
+    Title,
+}
+
+#[derive(Clone,Copy,PartialEq,Eq,Debug,Default)]
+#[cfg_attr(feature = "configuration", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "configuration", serde(rename_all = "kebab-case"))]
+pub enum TitleAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
 }
@@ -122,6 +122,14 @@ impl WMController<xlib::Window> for X11Controller {
         }
     }
 
+    fn insert_workspace(&self, workspace: u32) -> Result<()> {
+        require_ewmh_atom(self.display, MarsInsertWorkspace)?;
+        let mut data = xlib::ClientMessageData::new();
+        data.set_long(0, workspace.into());
+        send_client_message(self.display, MarsInsertWorkspace, 0, data);
+        Ok(())
+    }
+
     fn pin_window(&self, window: xlib::Window, mode: SettingMode) -> Result<()> {
         require_ewmh_atom(self.display, NetWMDesktop)?;
         match mode {
@@ -146,6 +154,25 @@ impl WMController<xlib::Window> for X11Controller {
         Ok(())
     }
 
+    fn remove_workspace(&self, workspace: u32) -> Result<()> {
+        require_ewmh_atom(self.display, MarsRemoveWorkspace)?;
+        let mut data = xlib::ClientMessageData::new();
+        data.set_long(0, workspace.into());
+        send_client_message(self.display, MarsRemoveWorkspace, 0, data);
+        Ok(())
+    }
+
+    fn rename_workspace(&self, workspace: u32, name: String) -> Result<()> {
+        let mut names = self.workspaces()?;
+        match names.get_mut(workspace as usize) {
+            Some(old_name) => *old_name = name,
+            None => return Err(MarsError::invalid_input(format!("workspace {} does not exist", workspace))),
+        }
+        self.root.x11_set_text_list_property(self.display, NetDesktopNames, &names);
+        unsafe { xlib::XFlush(self.display); }
+        Ok(())
+    }
+
     fn send_window_to_workspace(&self, window: xlib::Window, workspace: u32) -> Result<()> {
         require_ewmh_atom(self.display, NetWMDesktop)?;
         let mut data = xlib::ClientMessageData::new();
@@ -13,6 +13,10 @@ use crate::platforms::x11::draw::canvas::X11Canvas;
 pub const MIN_SIZE: (u32, u32) = (10, 10);
 pub const MAX_SIZE: (u32, u32) = (u32::MAX, u32::MAX);
 
+// subscribe to StructureNotifyMask for MapNotify events
+// subscribe to ExposureMask for Expose events
+const WIDGET_EVENT_MASK: i64 = xlib::StructureNotifyMask | xlib::ExposureMask | xlib::ButtonPressMask;
+
 
 pub trait WidgetEventHandler {
     fn handle_action_event(&self, event: WidgetEvent, already_handled: bool) -> bool;
@@ -72,6 +76,8 @@ pub struct X11TextWidget {
     vpad: u32,
     fg_color: u64,
     bg_color: u64,
+    hover_color: Option<u64>,
+    hovered: bool,
 }
 
 impl<W: Widget> X11FlowLayoutWidget<W> {
@@ -171,6 +177,13 @@ impl<W: Widget> X11FlowLayoutWidget<W> {
         self.canvas.match_resize();
     }
 
+    pub fn set_background(&mut self, color: u64) -> Result<()> {
+        self.canvas.set_foreground(color)
+            .and(self.canvas.set_background(color))?;
+        self.redraw();
+        Ok(())
+    }
+
     pub fn truncate_children(&mut self, len: usize) {
         self.children.truncate(len);
     }
@@ -201,6 +214,8 @@ impl X11TextWidget {
             hpad: params.hpad,
             vpad: params.vpad,
             fg_color, bg_color,
+            hover_color: None,
+            hovered: false,
         };
 
         widget.resize_to_content();
@@ -248,11 +263,57 @@ impl X11TextWidget {
         Ok(())
     }
 
+    /// Use a different background color while the pointer is above the widget
+    pub fn set_hover_color(&mut self, color: u64) {
+        self.hover_color = Some(color);
+        unsafe {
+            let mask = WIDGET_EVENT_MASK | xlib::EnterWindowMask | xlib::LeaveWindowMask;
+            xlib::XSelectInput(self.display, self.window, mask);
+        }
+    }
+
+    pub fn set_hovered(&mut self, state: bool) {
+        if state != self.hovered {
+            self.hovered = state;
+            self.redraw();
+        }
+    }
+
     pub fn set_label(&mut self, label: String) {
         self.label = label;
         self.resize_to_content();
         self.redraw();
     }
+
+    /// Set the label and cut out its middle if it is wider than `max_width`
+    pub fn set_label_truncated(&mut self, label: &str, max_width: u32) {
+        let max_text_width = max_width.saturating_sub(2 * self.hpad);
+        let fits = |canvas: &X11Canvas, text: &str| canvas.text_size(text)
+            .map(|(w, _)| w <= max_text_width).unwrap_or(true);
+
+        if fits(&self.canvas, label) {
+            self.set_label(label.to_owned());
+            return;
+        }
+
+        // find the maximum number of characters that fit around the ellipsis
+        let chars: Vec<char> = label.chars().collect();
+        let ellipsize = |n: usize| {
+            let head: String = chars[..n.div_ceil(2)].iter().collect();
+            let tail: String = chars[chars.len() - n / 2..].iter().collect();
+            format!("{}…{}", head, tail)
+        };
+        let (mut low, mut high) = (0, chars.len());
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            if fits(&self.canvas, &ellipsize(mid)) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        self.set_label(ellipsize(low));
+    }
 }
 
 impl X11WidgetParams {
@@ -358,7 +419,11 @@ impl Widget for X11TextWidget {
     }
 
     fn redraw(&mut self) {
-        self.canvas.fill_rectangle_with(0, 0, self.width, self.height, self.bg_color);
+        let bg_color = match self.hover_color {
+            Some(color) if self.hovered => color,
+            _ => self.bg_color,
+        };
+        self.canvas.fill_rectangle_with(0, 0, self.width, self.height, bg_color);
         if let Ok(text_size) = self.canvas.text_size(&self.label) {
             // center text if possible
             // the min() call is necessary as the text_size might be bigger than the max size
@@ -387,6 +452,14 @@ impl Widget for X11TextWidget {
                         self.redraw();
                         None
                     }
+                    xlib::EnterNotify => {
+                        self.set_hovered(true);
+                        None
+                    },
+                    xlib::LeaveNotify => {
+                        self.set_hovered(false);
+                        None
+                    },
                     _ => None,
                 };
 
@@ -444,10 +517,7 @@ pub fn create_widget_window(display: *mut xlib::Display, parent: xlib::Window, d
                                        xlib::XBlackPixel(display, screen),
                                        xlib::XWhitePixel(display, screen));
 
-        // subscribe to StructureNotifyMask for MapNotify events
-        // subscribe to ExposureMask for Expose events
-        let mask = xlib::StructureNotifyMask | xlib::ExposureMask | xlib::ButtonPressMask;
-        xlib::XSelectInput(display, win, mask);
+        xlib::XSelectInput(display, win, WIDGET_EVENT_MASK);
 
         // reparent window
         let status = xlib::XReparentWindow(display, win, parent, dimensions.x(), dimensions.y());
@@ -10,9 +10,11 @@ enum_with_values! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     vis pub enum X11Atom {
         // ICCCM
+        ResourceManager,
         UTF8String,
         WMClass,
         WMDeleteWindow,
+        WMHints,
         WMIconName,
         WMIconSize,
         WMName,
@@ -39,6 +41,7 @@ enum_with_values! {
         NetWMName,
         NetWMState,
         NetWMStateFullscreen,
+        NetWMWindowOpacity,
         NetWMWindowType,
         NetWMWindowTypeDesktop,
         NetWMWindowTypeDialog,
@@ -59,6 +62,11 @@ enum_with_values! {
         // TODO add custom hints for pinned, tiled
         // MARS (custom)
         MarsCenter,
+        MarsInsertWorkspace,
+        MarsKeyPrefix,
+        MarsMode,
+        MarsReloadTheme,
+        MarsRemoveWorkspace,
         MarsStatus,
         MarsWMStateTiled
     }
@@ -67,9 +75,11 @@ enum_with_values! {
 impl Display for X11Atom {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         let string = match self {
+            X11Atom::ResourceManager => "RESOURCE_MANAGER",
             X11Atom::UTF8String => "UTF8_STRING",
             X11Atom::WMClass => "WM_CLASS",
             X11Atom::WMDeleteWindow => "WM_DELETE_WINDOW",
+            X11Atom::WMHints => "WM_HINTS",
             X11Atom::WMIconName => "WM_ICON_NAME",
             X11Atom::WMIconSize => "WM_ICON_SIZE",
             X11Atom::WMProtocols => "WM_PROTOCOLS",
@@ -95,6 +105,7 @@ impl Display for X11Atom {
             X11Atom::NetWMName => "_NET_WM_NAME",
             X11Atom::NetWMState => "_NET_WM_STATE",
             X11Atom::NetWMStateFullscreen => "_NET_WM_STATE_FULLSCREEN",
+            X11Atom::NetWMWindowOpacity => "_NET_WM_WINDOW_OPACITY",
             X11Atom::NetWMWindowType => "_NET_WM_WINDOW_TYPE",
             X11Atom::NetWMWindowTypeDock => "_NET_WM_WINDOW_TYPE_DOCK",
             X11Atom::NetWMWindowTypeDesktop => "_NET_WM_WINDOW_TYPE_DESKTOP",
@@ -111,6 +122,11 @@ impl Display for X11Atom {
             X11Atom::XembedInfo => "_XEMBED_INFO",
 
             X11Atom::MarsCenter => "_MARS_CENTER",
+            X11Atom::MarsInsertWorkspace => "_MARS_INSERT_WORKSPACE",
+            X11Atom::MarsKeyPrefix => "_MARS_KEY_PREFIX",
+            X11Atom::MarsMode => "_MARS_MODE",
+            X11Atom::MarsReloadTheme => "_MARS_RELOAD_THEME",
+            X11Atom::MarsRemoveWorkspace => "_MARS_REMOVE_WORKSPACE",
             X11Atom::MarsStatus => "_MARS_STATUS",
             X11Atom::MarsWMStateTiled => "_MARS_WM_STATE_TILED",
         };
@@ -2,9 +2,11 @@ extern crate x11;
 
 use std::collections::VecDeque;
 use std::ffi::*;
+use std::io;
 use std::mem::MaybeUninit;
 use std::ptr;
 use std::slice;
+use std::time::Duration;
 use x11::xinerama;
 use x11::xlib;
 use x11::xrandr;
@@ -18,6 +20,7 @@ use crate::platforms::x11::misc::{
 };
 
 pub mod atoms;
+pub mod shape;
 pub mod window;
 
 
@@ -82,6 +85,24 @@ pub fn alternative_modifiers(modifiers: u32) -> Vec<u32> {
     ]
 }
 
+/// Waits until events are available on the connection or the timeout expires.
+/// Returns false if the timeout expired or polling the connection failed.
+pub fn await_event(display: *mut xlib::Display, timeout: Duration) -> bool {
+    let mut fd = libc::pollfd {
+        fd: unsafe { xlib::XConnectionNumber(display) },
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout_ms = timeout.as_millis().try_into().unwrap_or(c_int::MAX);
+    loop {
+        match unsafe { libc::poll(&mut fd, 1, timeout_ms) } {
+            -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+            -1 => return false,
+            nready => return nready > 0,
+        }
+    }
+}
+
 /// Waits for MapNotify on the specified window.
 /// Discards all events before the MapNotify.
 pub fn await_map_notify(display: *mut xlib::Display, window: xlib::Window) {
@@ -187,6 +208,19 @@ pub fn get_keysym(name: &str) -> xlib::KeySym {
     }
 }
 
+/// Check whether a [xlib::KeySym] belongs to a modifier key (like `Shift_L` or `Super_R`)
+pub fn is_modifier_key(keysym: u32) -> bool {
+    const XK_SHIFT_L: u32 = 0xffe1;
+    const XK_HYPER_R: u32 = 0xffee;
+    const XK_ISO_LOCK: u32 = 0xfe01;
+    const XK_ISO_LEVEL5_LOCK: u32 = 0xfe13;
+    const XK_MODE_SWITCH: u32 = 0xff7e;
+    const XK_NUM_LOCK: u32 = 0xff7f;
+    (XK_SHIFT_L..=XK_HYPER_R).contains(&keysym)
+        || (XK_ISO_LOCK..=XK_ISO_LEVEL5_LOCK).contains(&keysym)
+        || keysym == XK_MODE_SWITCH || keysym == XK_NUM_LOCK
+}
+
 pub extern "C" fn on_error_dummy(_display: *mut xlib::Display, _error: *mut xlib::XErrorEvent) -> c_int {
     0
 }
@@ -0,0 +1,77 @@
+extern crate x11;
+
+use std::cmp;
+use std::ffi::*;
+use std::ptr;
+use x11::xlib;
+
+use crate::platforms::x11::misc::XLIB_NONE;
+
+
+// see X11/extensions/shape.h
+const SHAPE_SET: c_int = 0;
+const SHAPE_BOUNDING: c_int = 0;
+const SHAPE_CLIP: c_int = 1;
+
+#[link(name = "Xext")]
+unsafe extern "C" {
+    fn XShapeQueryExtension(display: *mut xlib::Display, event_base: *mut c_int, error_base: *mut c_int) -> xlib::Bool;
+    fn XShapeCombineMask(display: *mut xlib::Display, dest: xlib::Window, dest_kind: c_int,
+                         x_off: c_int, y_off: c_int, src: xlib::Pixmap, op: c_int);
+}
+
+
+/// Check whether the X server supports the Shape extension
+pub fn shape_supported(display: *mut xlib::Display) -> bool {
+    let mut event_base = 0;
+    let mut error_base = 0;
+    unsafe { XShapeQueryExtension(display, &mut event_base, &mut error_base) != 0 }
+}
+
+/// Round the corners of a window including its border
+///
+/// The bounding shape follows the outer edge of the border, while the clip shape cuts the contents along its
+/// inner edge, so the border keeps its width around the corners.
+pub fn shape_round_corners(display: *mut xlib::Display, window: xlib::Window, width: u32, height: u32,
+                           border_width: u32, radius: u32) {
+    let bw = border_width as i32;
+    let bounding = rounded_mask(display, window, width + 2 * border_width, height + 2 * border_width, radius);
+    let clip = rounded_mask(display, window, width, height, radius.saturating_sub(border_width));
+    unsafe {
+        XShapeCombineMask(display, window, SHAPE_BOUNDING, -bw, -bw, bounding, SHAPE_SET);
+        XShapeCombineMask(display, window, SHAPE_CLIP, 0, 0, clip, SHAPE_SET);
+        xlib::XFreePixmap(display, bounding);
+        xlib::XFreePixmap(display, clip);
+    }
+}
+
+/// Restore the rectangular shape of a window
+pub fn shape_reset(display: *mut xlib::Display, window: xlib::Window) {
+    unsafe {
+        XShapeCombineMask(display, window, SHAPE_BOUNDING, 0, 0, XLIB_NONE, SHAPE_SET);
+        XShapeCombineMask(display, window, SHAPE_CLIP, 0, 0, XLIB_NONE, SHAPE_SET);
+    }
+}
+
+fn rounded_mask(display: *mut xlib::Display, window: xlib::Window, width: u32, height: u32, radius: u32) -> xlib::Pixmap {
+    let radius = cmp::min(radius, cmp::min(width, height) / 2);
+    let diameter = 2 * radius;
+    let (w, h, r, d) = (width as i32, height as i32, radius as i32, diameter as i32);
+    unsafe {
+        let pixmap = xlib::XCreatePixmap(display, window, width, height, 1);
+        let gc = xlib::XCreateGC(display, pixmap, 0, ptr::null_mut());
+
+        xlib::XSetForeground(display, gc, 0);
+        xlib::XFillRectangle(display, pixmap, gc, 0, 0, width, height);
+
+        xlib::XSetForeground(display, gc, 1);
+        for (x, y) in [(0, 0), (w - d, 0), (0, h - d), (w - d, h - d)] {
+            xlib::XFillArc(display, pixmap, gc, x, y, diameter, diameter, 0, 360 * 64);
+        }
+        xlib::XFillRectangle(display, pixmap, gc, r, 0, width - diameter, height);
+        xlib::XFillRectangle(display, pixmap, gc, 0, r, width, height - diameter);
+
+        xlib::XFreeGC(display, gc);
+        pixmap
+    }
+}
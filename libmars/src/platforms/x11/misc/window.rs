@@ -32,6 +32,7 @@ pub trait X11Window {
     fn x11_geometry(&self, display: *mut xlib::Display) -> Result<(u64, i32, i32, u32, u32, u32, u32)>;
     fn x11_get_window_types(&self, display: *mut xlib::Display) -> Vec<X11Atom>;
     fn x11_is_transient_for(&self, display: *mut xlib::Display) -> Option<xlib::Window>;
+    fn x11_is_urgent(&self, display: *mut xlib::Display) -> bool;
     fn x11_map(&self, display: *mut xlib::Display);
     fn x11_message(&self, display: *mut xlib::Display, msg_type: atoms::X11Atom, msg_format: c_int, msg_data: xlib::ClientMessageData);
     fn x11_unmap(&self, display: *mut xlib::Display);
@@ -297,6 +298,18 @@ impl X11Window for xlib::Window {
         }
     }
 
+    fn x11_is_urgent(&self, display: *mut xlib::Display) -> bool {
+        unsafe {
+            let hints = xlib::XGetWMHints(display, *self);
+            if hints.is_null() {
+                return false;
+            }
+            let urgent = (*hints).flags & xlib::XUrgencyHint != 0;
+            xlib::XFree(hints as *mut c_void);
+            urgent
+        }
+    }
+
     fn x11_map(&self, display: *mut xlib::Display) {
         unsafe {
             xlib::XMapWindow(display, *self);
@@ -347,7 +360,9 @@ impl X11Window for xlib::Window {
     }
 
     fn x11_wm_name(&self, display: *mut xlib::Display) -> Result<String> {
-        self.x11_read_property_string(display, WMName)
+        // prefer the UTF-8 encoded EWMH name
+        self.x11_read_property_string(display, NetWMName)
+            .or_else(|_| self.x11_read_property_string(display, WMName))
     }
 
     fn x11_wm_normal_hints(&self, display: *mut xlib::Display) -> Result<(xlib::XSizeHints, c_long)> {
@@ -7,6 +7,7 @@ use std::mem::MaybeUninit;
 use std::ptr;
 use std::rc::Rc;
 use std::slice;
+use std::time::{Duration, Instant};
 use x11::xlib;
 use x11::xrandr;
 
@@ -20,6 +21,7 @@ use crate::platforms::x11::misc::{
     *,
     atoms::*,
     atoms::X11Atom::*,
+    shape::shape_supported,
     window::*,
 };
 
@@ -50,9 +52,11 @@ pub struct X11Backend<A: PartialEq> {
     display: *mut xlib::Display,
     root: u64,
     xrandr: XRandrInfo,
+    shape_supported: bool,
     monitors: Vec<MonitorConfig>,
     wmcheck_win: u64,
     unmanaged_clients: Vec<UnmanagedClient>,
+    timeout: Option<Instant>,
 }
 
 struct XRandrInfo {
@@ -63,7 +67,7 @@ struct XRandrInfo {
 
 
 const MAX_FRAMERATE: u64 = 120;
-const SUPPORTED_ATOMS: &[X11Atom; 22] = & [
+const SUPPORTED_ATOMS: &[X11Atom; 24] = & [
     NetActiveWindow,
     NetClientList,
     NetClientListStacking,
@@ -86,6 +90,8 @@ const SUPPORTED_ATOMS: &[X11Atom; 22] = & [
     NetWorkarea,
 
     MarsCenter,
+    MarsInsertWorkspace,
+    MarsRemoveWorkspace,
     MarsWMStateTiled,
 ];
 
@@ -108,9 +114,11 @@ impl<A: PartialEq + Default> X11Backend<A> {
                 display,
                 root,
                 xrandr: XRandrInfo::query(display),
+                shape_supported: shape_supported(display),
                 monitors: Vec::new(),
                 wmcheck_win: 0,
                 unmanaged_clients: Vec::new(),
+                timeout: None,
             };
 
             // For debugging:
@@ -132,7 +140,8 @@ impl<A: PartialEq + Default> X11Backend<A> {
             let mut attributes: MaybeUninit<xlib::XSetWindowAttributes> = MaybeUninit::uninit();
             (*attributes.as_mut_ptr()).cursor = xlib::XCreateFontCursor(display, CURSOR_NORMAL);
             (*attributes.as_mut_ptr()).event_mask = xlib::SubstructureRedirectMask |
-                xlib::SubstructureNotifyMask | xlib::StructureNotifyMask | xlib::KeyPressMask | xlib::ButtonPressMask;
+                xlib::SubstructureNotifyMask | xlib::StructureNotifyMask | xlib::KeyPressMask | xlib::ButtonPressMask
+                | xlib::PropertyChangeMask;
             xlib::XChangeWindowAttributes(display, root, xlib::CWEventMask | xlib::CWCursor, attributes.as_mut_ptr());
             xlib::XSync(display, xlib::False);
             xlib::XSetErrorHandler(Some(on_error));
@@ -212,6 +221,7 @@ impl<A: PartialEq + Default> X11Backend<A> {
                 xlib::EnterNotify => self.on_enter_notify(wm, event.crossing),
                 xlib::Expose => self.on_expose_event(wm, event.expose),
                 xlib::KeyPress => self.on_key_press(wm, event.key),
+                xlib::KeyRelease => self.on_key_release(wm, event.key),
                 xlib::LeaveNotify => self.on_leave_notify(wm, event.crossing),
                 xlib::MapRequest => self.on_map_request(wm, event.map_request),
                 xlib::MapNotify => self.on_map_notify(wm, event.map),
@@ -279,7 +289,7 @@ impl<A: PartialEq + Default> X11Backend<A> {
         //     None => None,
         // };
 
-        if let Ok(mut client) = X11Client::new(self.display, self.root, window, is_dialog) {
+        if let Ok(mut client) = X11Client::new(self.display, self.root, window, is_dialog, self.shape_supported) {
             // client.apply_size_hints();
             client.apply_motif_hints();
 
@@ -339,7 +349,14 @@ impl<A: PartialEq + Default> X11Backend<A> {
         let modifiers = sanitize_modifiers(event.state);
 
         if let Some(client_rc) = Self::client_by_frame(wm, event.window) {
-            wm.handle_button(self, modifiers, event.button, ButtonTarget::Frame, Some(client_rc));
+            let title_button = client_rc.borrow().title_button_index(event.window);
+            if let Some(index) = title_button {
+                wm.handle_title_button(self, client_rc, index, event.button);
+            } else if client_rc.borrow().title_window() == Some(event.window) {
+                wm.handle_button(self, modifiers, event.button, ButtonTarget::Title, Some(client_rc));
+            } else {
+                wm.handle_button(self, modifiers, event.button, ButtonTarget::Frame, Some(client_rc));
+            }
         } else if let Some(client_rc) = Self::client_by_window(wm, event.window) {
             wm.handle_button(self, modifiers, event.button, ButtonTarget::Window, Some(client_rc));
         } else if event.window == self.root {
@@ -368,6 +385,11 @@ impl<A: PartialEq + Default> X11Backend<A> {
                         wm.switch_workspace(self, workspace);
                     }
                 },
+                NetNumberOfDesktops => {
+                    if let Ok(count) = event.data.get_long(0).try_into() {
+                        wm.set_workspace_count(self, count);
+                    }
+                },
                 NetWMDesktop => {
                     if let Some(client_rc) = Self::client_by_window(wm, event.window) {
                         let workspace = event.data.get_long(0);
@@ -407,6 +429,16 @@ impl<A: PartialEq + Default> X11Backend<A> {
                         wm.center_client(self, client_rc);
                     }
                 },
+                MarsInsertWorkspace => {
+                    if let Ok(workspace) = event.data.get_long(0).try_into() {
+                        wm.insert_workspace(self, workspace);
+                    }
+                },
+                MarsRemoveWorkspace => {
+                    if let Ok(workspace) = event.data.get_long(0).try_into() {
+                        wm.remove_workspace(self, workspace);
+                    }
+                },
                 _ => (),
             }
         }
@@ -525,6 +557,10 @@ impl<A: PartialEq + Default> X11Backend<A> {
         // }
 
         let client_option = if let Some(client_rc) = Self::client_by_frame(wm, event.window) {
+            let title_button = client_rc.borrow().title_button_index(event.window);
+            if let Some(index) = title_button {
+                client_rc.borrow_mut().set_title_button_hovered(index, true);
+            }
             Some(client_rc)
         } else if let Some(client_rc) = Self::client_by_window(wm, event.window) {
             Some(client_rc)
@@ -540,7 +576,7 @@ impl<A: PartialEq + Default> X11Backend<A> {
 
     fn on_expose_event(&mut self, wm: &mut (impl WindowManager<Self, A> + ?Sized), event: xlib::XExposeEvent) {
         if let Some(client_rc) = Self::client_by_frame(wm, event.window) {
-            client_rc.borrow_mut().update_title();
+            client_rc.borrow_mut().redraw_title_bar();
         }
     }
 
@@ -557,7 +593,26 @@ impl<A: PartialEq + Default> X11Backend<A> {
         wm.handle_key(self, modifiers, key, client_opt)
     }
 
-    fn on_leave_notify(&mut self, _wm: &mut (impl WindowManager<Self, A> + ?Sized), _event: xlib::XCrossingEvent) {
+    fn on_key_release(&mut self, wm: &mut (impl WindowManager<Self, A> + ?Sized), event: xlib::XKeyEvent) {
+        let keysym = unsafe {
+            xlib::XKeycodeToKeysym(self.display, event.keycode.try_into().unwrap(), 0)
+        };
+
+        // the event state still contains the modifier that is being released
+        let released_mask = self.modifier_mask(event.keycode);
+        let modifiers = sanitize_modifiers(event.state) & !released_mask;
+        let key: u32 = keysym.try_into().unwrap();
+        wm.handle_key_release(self, modifiers, key)
+    }
+
+    fn on_leave_notify(&mut self, wm: &mut (impl WindowManager<Self, A> + ?Sized), event: xlib::XCrossingEvent) {
+        if let Some(client_rc) = Self::client_by_frame(wm, event.window) {
+            let title_button = client_rc.borrow().title_button_index(event.window);
+            if let Some(index) = title_button {
+                client_rc.borrow_mut().set_title_button_hovered(index, false);
+            }
+        }
+
         //print_event!(wm, event);
         // if let Some(client_rc) = Self::client_by_frame(wm, event.window) {
         //     println!("LeaveNotify on frame for client {}", client_rc.borrow().window());
@@ -615,7 +670,42 @@ impl<A: PartialEq + Default> X11Backend<A> {
     pub fn on_property_notify(&mut self, wm: &mut (impl WindowManager<Self, A> + ?Sized), event: xlib::XPropertyEvent) {
         if let Some(client_rc) = Self::client_by_window(wm, event.window)
             && let Some(atom) = X11Atom::from_xlib_atom(self.display, event.atom)
-                && atom == WMName { client_rc.borrow_mut().update_title() }
+                && matches!(atom, WMName | NetWMName | WMHints) {
+                    client_rc.borrow_mut().update_property(atom);
+                    wm.handle_client_update(self, client_rc);
+                }
+
+        // the X resources may contain colors of the theme
+        if event.window == self.root && X11Atom::from_xlib_atom(self.display, event.atom) == Some(ResourceManager) {
+            wm.reload_theme(self);
+        }
+
+        // pagers rename workspaces by changing the property directly
+        if event.window == self.root && event.state == xlib::PropertyNewValue
+            && X11Atom::from_xlib_atom(self.display, event.atom) == Some(NetDesktopNames)
+                && let Ok(names) = self.root.x11_get_text_list_property(self.display, NetDesktopNames) {
+                    wm.rename_workspaces(self, names);
+                }
+    }
+
+    /// Get the modifier mask a keycode is mapped to (0 for regular keys)
+    fn modifier_mask(&self, keycode: u32) -> u32 {
+        unsafe {
+            let modmap = xlib::XGetModifierMapping(self.display);
+            if modmap.is_null() {
+                return 0;
+            }
+
+            let keys_per_mod = (*modmap).max_keypermod as usize;
+            let keycodes = slice::from_raw_parts((*modmap).modifiermap, 8 * keys_per_mod);
+            let mask = keycodes.iter()
+                .position(|kc| *kc as u32 == keycode && *kc != 0)
+                .map(|i| 1 << (i / keys_per_mod))
+                .unwrap_or(0);
+
+            xlib::XFreeModifiermap(modmap);
+            mask
+        }
     }
 
     fn remove_unmanaged_client(&mut self, wm: &mut (impl WindowManager<Self, A> + ?Sized), window: xlib::Window) -> bool {
@@ -660,7 +750,10 @@ impl<A: PartialEq + Default> X11Backend<A> {
 
 
     fn client_by_frame(wm: &(impl WindowManager<Self, A> + ?Sized), frame: u64) -> Option<Rc<RefCell<X11Client<A>>>> {
-        return wm.clients().find(|c| c.borrow().frame() == frame || c.borrow().title_window() == Some(frame)).cloned();
+        return wm.clients().find(|c| {
+            let client = c.borrow();
+            client.frame() == frame || client.title_window() == Some(frame) || client.title_button_index(frame).is_some()
+        }).cloned();
     }
 
     fn client_by_window(wm: &(impl WindowManager<Self, A> + ?Sized), window: u64) -> Option<Rc<RefCell<X11Client<A>>>> {
@@ -709,6 +802,19 @@ impl<A: PartialEq + Default> Backend<A> for X11Backend<A> {
         self.root.x11_replace_property_long(self.display, NetCurrentDesktop, xlib::XA_CARDINAL, data);
     }
 
+    fn export_key_prefix(&self, prefix: &str) {
+        self.root.x11_set_text_list_property(self.display, MarsKeyPrefix, &[prefix.to_owned()]);
+    }
+
+    fn export_mode(&self, mode: &str) {
+        self.root.x11_set_text_list_property(self.display, MarsMode, &[mode.to_owned()]);
+    }
+
+    fn export_theme_change(&self) {
+        // the value does not matter as clients listen for the property change itself
+        self.root.x11_replace_property_long(self.display, MarsReloadTheme, xlib::XA_CARDINAL, &[0]);
+    }
+
     fn export_workspaces(&self, mut workspaces: Vec<(String, Dimensions, Dimensions)>) {
         let nworkspaces: u64 = workspaces.len().try_into().unwrap();
         let mut names = Vec::new();
@@ -739,6 +845,13 @@ impl<A: PartialEq + Default> Backend<A> for X11Backend<A> {
         self.monitors.clone()
     }
 
+    fn grab_keyboard(&self) -> bool {
+        unsafe {
+            xlib::XGrabKeyboard(self.display, self.root, xlib::True, xlib::GrabModeAsync,
+                                xlib::GrabModeAsync, xlib::CurrentTime) == xlib::GrabSuccess
+        }
+    }
+
     fn handle_existing_windows(&mut self, wm: &mut WM<A>) {
         unsafe {
             xlib::XGrabServer(self.display);
@@ -900,6 +1013,16 @@ impl<A: PartialEq + Default> Backend<A> for X11Backend<A> {
         }
     }
 
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout.map(|t| Instant::now() + t);
+    }
+
+    fn ungrab_keyboard(&self) {
+        unsafe {
+            xlib::XUngrabKeyboard(self.display, xlib::CurrentTime);
+        }
+    }
+
     fn warp_pointer(&self, x: i32, y: i32) {
         unsafe {
             // this might fail (best effort)
@@ -910,6 +1033,16 @@ impl<A: PartialEq + Default> Backend<A> for X11Backend<A> {
     fn run(mut self, wm: &mut WM<A>) {
         loop {
             unsafe {
+                // wait for pending timeouts to expire if no events are queued
+                if let Some(deadline) = self.timeout && xlib::XPending(self.display) == 0 {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() || !await_event(self.display, remaining) {
+                        self.timeout = None;
+                        wm.handle_timeout(&mut self);
+                        continue;
+                    }
+                }
+
                 let mut event: MaybeUninit<xlib::XEvent> = MaybeUninit::uninit();
                 xlib::XNextEvent(self.display, event.as_mut_ptr());
                 self.handle_xevent(wm, event.assume_init());
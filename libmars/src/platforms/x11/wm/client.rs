@@ -12,6 +12,7 @@ use crate::platforms::x11::{
     misc::*,
     misc::atoms::*,
     misc::atoms::X11Atom::*,
+    misc::shape::*,
     misc::window::*,
     wm::*,
 };
@@ -26,6 +27,13 @@ pub struct X11Client<A: PartialEq> {
     window: u64,
     frame: u64,
     title_widget: Option<X11TextWidget>,
+    title_label: String,  // untruncated text of the title widget
+    class: String,
+    instance: String,
+    title: String,
+    urgent: bool,
+    title_alignment: TitleAlignment,
+    title_buttons: Vec<X11TextWidget>,
     attributes: A,
 
     orig_pos: (i32, i32), // position prior to reparenting
@@ -35,18 +43,23 @@ pub struct X11Client<A: PartialEq> {
     fw: (u32, u32, u32, u32),        // frame width
 
     actively_reparenting: bool,
+    decoration_hidden: bool,
     dont_decorate: bool,
     fullscreen: bool,
     is_dialog: bool,
     visible: bool,
 
     frame_color: u64,
+    corner_radius: u32,
+    shape_supported: bool,
+    shape: Option<(u32, u32, u32, u32)>,  // size, border width and radius of the applied shape mask
     saved_decorations: Option<Decoration>,
     saved_dimensions: Option<Dimensions>,
 }
 
 impl<A: Default + PartialEq> X11Client<A> {
-    pub fn new(display: *mut xlib::Display, root: u64, window: xlib::Window, is_dialog: bool) -> Result<X11Client<A>> {
+    pub fn new(display: *mut xlib::Display, root: u64, window: xlib::Window, is_dialog: bool,
+               shape_supported: bool) -> Result<X11Client<A>> {
         let attributes = window.x11_attributes(display)?;
         let x = attributes.x;
         let y = attributes.y;
@@ -72,18 +85,24 @@ impl<A: Default + PartialEq> X11Client<A> {
             xlib::XSelectInput(display, window, xlib::EnterWindowMask | xlib::LeaveWindowMask | xlib::PropertyChangeMask);
         }
 
-        let name = match window.x11_class_hint(display) {
-            Ok((name, class)) => format!("{}::{} [0x{:x}]", name, class, window),
+        let (name, instance, class) = match window.x11_class_hint(display) {
+            Ok((instance, class)) => (format!("{}::{} [0x{:x}]", instance, class, window), instance, class),
             Err(msg) => {
                 println!("Unable to get class hint: {}", msg);
-                format!("0x{:x}", window)
+                (format!("0x{:x}", window), String::default(), String::default())
             },
         };
+        let title = window.x11_wm_name(display).unwrap_or_default();
+        let urgent = window.x11_is_urgent(display);
 
         Ok( X11Client {
             name,
             display, root, window, frame,
             title_widget: None,
+            title_label: String::new(),
+            class, instance, title, urgent,
+            title_alignment: TitleAlignment::default(),
+            title_buttons: Vec::new(),
             attributes: A::default(),
 
             orig_pos: (x, y),
@@ -93,12 +112,16 @@ impl<A: Default + PartialEq> X11Client<A> {
             fw: (0, 0, 0, 0),
 
             actively_reparenting: false,
+            decoration_hidden: false,
             dont_decorate: false,
             fullscreen: false,
             is_dialog,
             visible: false,
 
             frame_color: 0x000000,
+            corner_radius: 0,
+            shape_supported,
+            shape: None,
             saved_decorations: None,
             saved_dimensions: None,
         } )
@@ -106,6 +129,15 @@ impl<A: Default + PartialEq> X11Client<A> {
 }
 
 impl<A: PartialEq> X11Client<A> {
+    /// Refresh the cached title or urgency after the corresponding property has changed
+    pub fn update_property(&mut self, atom: X11Atom) {
+        match atom {
+            WMName | NetWMName => self.title = self.window.x11_wm_name(self.display).unwrap_or_default(),
+            WMHints => self.urgent = self.window.x11_is_urgent(self.display),
+            _ => (),
+        }
+    }
+
     pub fn apply_motif_hints(&mut self) {
         let motif_atom = MotifWMHints.to_xlib_atom(self.display);
         if let Ok(hints) = self.x11_read_property_long(self.display, MotifWMHints, motif_atom)
@@ -228,13 +260,62 @@ impl<A: PartialEq> X11Client<A> {
         }
     }
 
-    pub fn update_title(&mut self) {
-        let title = self.title();
+    /// Keep the title bar buttons at the right end of the frame and fit the title into the remaining space
+    fn arrange_title_bar(&mut self) {
+        let mut x = (self.w - 2 * self.obw) as i32;
+        for button in self.title_buttons.iter_mut().rev() {
+            x -= button.size().0 as i32;
+            button.move_to(x, 0);
+        }
+
         if let Some(title_widget) = &mut self.title_widget {
-            title_widget.set_label(title);
+            let available = cmp::max(x, 0) as u32;
+            title_widget.set_label_truncated(&self.title_label, available);
+            let free_space = available.saturating_sub(title_widget.size().0);
+            let title_x = match self.title_alignment {
+                TitleAlignment::Left => 0,
+                TitleAlignment::Center => free_space / 2,
+                TitleAlignment::Right => free_space,
+            };
+            title_widget.move_to(title_x as i32, 0);
+        }
+    }
+
+    /// Apply the rounded corners to the frame (fullscreen and undecorated windows keep their rectangular shape)
+    ///
+    /// The masks are only recreated if the size of the frame has changed.
+    fn update_shape(&mut self) {
+        let undecorated = self.fullscreen || self.dont_decorate || self.decoration_hidden;
+        if self.corner_radius > 0 && !undecorated {
+            let shape = (self.w - 2 * self.obw, self.h - 2 * self.obw, self.obw, self.corner_radius);
+            if self.shape != Some(shape) {
+                shape_round_corners(self.display, self.frame, shape.0, shape.1, shape.2, shape.3);
+                self.shape = Some(shape);
+            }
+        } else if self.shape.take().is_some() {
+            shape_reset(self.display, self.frame);
         }
     }
 
+    pub fn redraw_title_bar(&mut self) {
+        if let Some(title_widget) = &mut self.title_widget {
+            title_widget.redraw();
+        }
+        for button in &mut self.title_buttons {
+            button.redraw();
+        }
+    }
+
+    pub fn set_title_button_hovered(&mut self, index: usize, state: bool) {
+        if let Some(button) = self.title_buttons.get_mut(index) {
+            button.set_hovered(state);
+        }
+    }
+
+    pub fn title_button_index(&self, window: u64) -> Option<usize> {
+        self.title_buttons.iter().position(|b| b.wid() == window)
+    }
+
     pub fn is_reparenting(&self) -> bool {
         self.actively_reparenting
     }
@@ -253,25 +334,41 @@ impl<A: PartialEq> X11Client<A> {
 }
 
 impl<A: PartialEq> Client<A> for X11Client<A> {
-    fn add_title(&mut self, font: &str, hpad: u32, vpad: u32, color: u64) -> Result<()> {
+    fn add_title(&mut self, font: &str, hpad: u32, vpad: u32, color: u64, alignment: TitleAlignment) -> Result<()> {
         let title = self.title();
         let params = X11WidgetParams::new(0, 0, hpad, vpad);
-        let mut widget = X11TextWidget::new(self.display, self.frame, params,
+        let widget = X11TextWidget::new(self.display, self.frame, params,
                                         title.clone(), font, color, self.frame_color)?;
         unsafe {
             xlib::XLowerWindow(self.display, widget.wid());
-            widget.set_label(title);
         }
 
         self.title_widget = Some(widget);
+        self.title_label = title;
+        self.title_alignment = alignment;
+        self.arrange_title_bar();
         Ok(())
     }
 
-    fn application(&self) -> String {
-        match self.x11_class_hint(self.display) {
-            Ok((_name, class)) => class,
-            Err(_) => String::default(),
+    fn add_title_buttons(&mut self, labels: &[String], font: &str, hpad: u32, vpad: u32, color: u64,
+                         hover_color: u64) -> Result<()> {
+        let params = X11WidgetParams::new(0, 0, hpad, vpad);
+        for label in labels {
+            let mut widget = X11TextWidget::new(self.display, self.frame, params,
+                                                label.clone(), font, color, self.frame_color)?;
+            widget.set_hover_color(hover_color);
+            unsafe {
+                xlib::XLowerWindow(self.display, widget.wid());
+            }
+            self.title_buttons.push(widget);
         }
+
+        self.arrange_title_bar();
+        Ok(())
+    }
+
+    fn application(&self) -> String {
+        self.class.clone()
     }
 
     fn attributes(&self) -> &A {
@@ -290,6 +387,7 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
             ButtonTarget::Window => self.window,
             // ButtonTarget::Frame => self.frame,
             ButtonTarget::Frame => return,  // already grabbed as we own the window
+            ButtonTarget::Title => return,  // the title widget receives all button presses
             ButtonTarget::Root => panic!("You can't bind actions to the root window through a client window"),
         };
 
@@ -350,6 +448,10 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
         self.fw
     }
 
+    fn id(&self) -> u64 {
+        self.window
+    }
+
     fn hide(&mut self) {
         if !self.visible {
             return;
@@ -388,6 +490,10 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
                                self.h - bw_north - bw_south)
     }
 
+    fn instance(&self) -> String {
+        self.instance.clone()
+    }
+
     fn is_centered_on_screen(&self, dimensions: Dimensions) -> bool {
         let (center_x, center_y) = dimensions.center();
 
@@ -403,6 +509,10 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
         self.fullscreen
     }
 
+    fn is_urgent(&self) -> bool {
+        self.urgent
+    }
+
     fn is_visible(&self) -> bool {
         self.visible
     }
@@ -430,6 +540,8 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
                                     inner_dimensions.x(), inner_dimensions.y(),
                                     inner_dimensions.w(), inner_dimensions.h());
         }
+        self.arrange_title_bar();
+        self.update_shape();
     }
 
     fn name(&self) -> &str {
@@ -446,6 +558,11 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
         }
     }
 
+    fn set_corner_radius(&mut self, radius: u32) {
+        self.corner_radius = if self.shape_supported { radius } else { 0 };
+        self.update_shape();
+    }
+
     fn set_dimensions(&mut self, dimensions: Dimensions) {
         self.move_resize(dimensions.x(), dimensions.y(), dimensions.w(), dimensions.h())
     }
@@ -461,6 +578,9 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
         if let Some(title_widget) = &mut self.title_widget {
             let _ignore_result = title_widget.set_background(color);
         }
+        for button in &mut self.title_buttons {
+            let _ignore_result = button.set_background(color);
+        }
     }
 
     fn set_frame_width(&mut self, width: (u32, u32, u32, u32)) {
@@ -472,6 +592,16 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
         self.move_resize(self.x - diff_west, self.y - diff_north, (self.w as i32 + diff_east + diff_west) as u32, (self.h as i32 + diff_north + diff_south) as u32);
     }
 
+    fn set_decoration_hidden(&mut self, state: bool) {
+        self.decoration_hidden = state;
+        if state {
+            self.remove_decoration();
+        } else if !self.fullscreen {
+            self.restore_decoration();
+        }
+        self.update_shape();
+    }
+
     fn set_fullscreen(&mut self, monitor_conf: &MonitorConfig) {
         let dimensions = monitor_conf.dimensions();
         self.saved_dimensions = Some(self.dimensions());
@@ -516,10 +646,26 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
         }
     }
 
+    fn set_title_button_hover_color(&mut self, color: u64) {
+        for button in &mut self.title_buttons {
+            button.set_hover_color(color);
+        }
+    }
+
     fn set_title_color(&mut self, color: u64) {
         if let Some(title_widget) = &mut self.title_widget {
             let _ignore_result = title_widget.set_foreground(color);
         }
+        for button in &mut self.title_buttons {
+            let _ignore_result = button.set_foreground(color);
+        }
+    }
+
+    fn set_title_label(&mut self, label: String) {
+        if label != self.title_label {
+            self.title_label = label;
+            self.arrange_title_bar();
+        }
     }
 
     fn show(&mut self) {
@@ -538,7 +684,7 @@ impl<A: PartialEq> Client<A> for X11Client<A> {
     }
 
     fn title(&self) -> String {
-        self.window.x11_wm_name(self.display).unwrap_or_default()
+        self.title.clone()
     }
 
     fn total_bw(&self) -> (u32, u32, u32, u32) {
@@ -722,6 +868,10 @@ impl<A: PartialEq> X11Window for X11Client<A> {
         self.window.x11_is_transient_for(display)
     }
 
+    fn x11_is_urgent(&self, display: *mut xlib::Display) -> bool {
+        self.window.x11_is_urgent(display)
+    }
+
     fn x11_map(&self, display: *mut xlib::Display) {
         self.window.x11_map(display);
     }
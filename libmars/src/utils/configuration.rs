@@ -4,6 +4,12 @@ use std::fs;
 use std::path;
 use serde::{Serialize, Deserialize};
 
+use crate::utils::theme::*;
+
+
+const THEME_DIR: &str = "marswm";
+const THEME_FILE: &str = "theme.yaml";
+
 
 fn deserialize_file<T: for<'a> Deserialize<'a>>(path: &path::Path) -> Result<T, (bool, String)> {
     let fs_result = fs::read(path);
@@ -50,3 +56,33 @@ pub fn read_config_file<T: for<'a> Deserialize<'a>>(config_name: &str, file_name
         Err(format!("configuration {} not found", file_name))
     }
 }
+
+/// Read the theme file shared by all components (`~/.config/marswm/theme.yaml`)
+///
+/// Returns `None` if there is no theme file.
+pub fn read_theme() -> Result<Option<Theme>, String> {
+    let config_dir = xdg::BaseDirectories::with_prefix(THEME_DIR);
+    let Some(path) = config_dir.find_config_file(THEME_FILE) else {
+        return Ok(None);
+    };
+
+    // entries of the theme may only refer to the default palette
+    Palette::default().make_current();
+    deserialize_file(&path).map(Some).map_err(|(_, msg)| msg)
+}
+
+/// Palette of the shared theme (falls back to the default palette if the theme is unavailable)
+///
+/// The palette becomes the current one, so configuration files read afterwards may use its names.
+pub fn read_palette() -> Palette {
+    let theme = match read_theme() {
+        Ok(theme) => theme.unwrap_or_default(),
+        Err(msg) => {
+            eprintln!("Unable to read theme: {}", msg);
+            Theme::default()
+        },
+    };
+    let palette = theme.palette();
+    palette.make_current();
+    palette
+}
@@ -3,3 +3,5 @@
 #[cfg(feature = "configuration")]
 pub mod configuration;
 pub mod macros;
+#[cfg(feature = "configuration")]
+pub mod theme;
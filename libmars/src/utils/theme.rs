@@ -0,0 +1,214 @@
+//! Colors and palettes shared between the window manager and accompanying software.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::fmt;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use x11::xlib;
+
+use crate::platforms::x11::misc::{open_display, close_display};
+
+
+/// Names of the palette entries that can be loaded from the X resources
+const XRESOURCE_COLORS: [&str; 18] = [
+    "color0", "color1", "color2", "color3", "color4", "color5", "color6", "color7",
+    "color8", "color9", "color10", "color11", "color12", "color13", "color14", "color15",
+    "background", "foreground",
+];
+
+/// Values of the palette entries if neither the theme nor the X resources define them
+const DEFAULT_PALETTE: [(&str, u64); 18] = [
+    ("color0", 0x262626), ("color1", 0xaf5f5f), ("color2", 0x5f875f), ("color3", 0xaf875f),
+    ("color4", 0x5f87af), ("color5", 0x875f87), ("color6", 0x5f8787), ("color7", 0xbcbcbc),
+    ("color8", 0x4e4e4e), ("color9", 0xd78787), ("color10", 0x87af87), ("color11", 0xd7af87),
+    ("color12", 0x87afd7), ("color13", 0xaf87af), ("color14", 0x87afaf), ("color15", 0xeeeeee),
+    ("background", 0x262626), ("foreground", 0xbcbcbc),
+];
+
+thread_local! {
+    /// Palette that named colors are checked against and initialized with when they are created
+    static CURRENT_PALETTE: RefCell<Palette> = RefCell::new(Palette::default());
+}
+
+
+/// A color given as `#rrggbb`/`#rrggbbaa` string, integer or name of a palette entry
+///
+/// The alpha channel is accepted but ignored, as windows are created with the default visual.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct Color {
+    name: Option<String>,
+    pixel: u64,
+}
+
+/// Named colors that can be referred to in configuration files
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct Palette {
+    colors: BTreeMap<String, u64>,
+}
+
+/// Contents of the shared theme file
+#[derive(Serialize,Deserialize,Default,Clone,Debug,PartialEq,Eq)]
+#[serde(default)]
+pub struct Theme {
+    /// load `color0` to `color15`, `background` and `foreground` from the X resources
+    pub xresources: bool,
+
+    /// named colors (these take precedence over the X resources)
+    pub palette: BTreeMap<String, Color>,
+}
+
+
+impl Color {
+    pub const fn rgb(pixel: u64) -> Color {
+        Color { name: None, pixel }
+    }
+
+    /// Color referring to an entry of the current palette
+    pub fn named(name: &str) -> Color {
+        let pixel = CURRENT_PALETTE.with_borrow(|p| p.get(name)).unwrap_or(0);
+        Color { name: Some(name.to_owned()), pixel }
+    }
+
+    /// Pixel value to use for drawing
+    pub fn pixel(&self) -> u64 {
+        self.pixel
+    }
+
+    /// Look up the value of named colors in the palette
+    pub fn resolve(&mut self, palette: &Palette) {
+        if let Some(name) = &self.name {
+            match palette.get(name) {
+                Some(pixel) => self.pixel = pixel,
+                None => eprintln!("Unknown color '{}'", name),
+            }
+        }
+    }
+
+    fn parse_hex(s: &str) -> Option<u64> {
+        let hex = s.strip_prefix('#')?;
+        let value = u64::from_str_radix(hex, 16).ok()?;
+        match hex.len() {
+            6 => Some(value),
+            8 => Some(value >> 8),  // drop alpha channel
+            _ => None,
+        }
+    }
+}
+
+impl Palette {
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.colors.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &str, pixel: u64) {
+        self.colors.insert(name.to_owned(), pixel);
+    }
+
+    /// Check names of colors against this palette when they are deserialized
+    pub fn make_current(&self) {
+        CURRENT_PALETTE.set(self.clone());
+    }
+
+    /// Overwrite palette entries with colors from the X resources (like `*color0` or `*.background`)
+    pub fn load_xresources(&mut self) {
+        let display = match open_display() {
+            Ok(display) => display,
+            Err(e) => {
+                eprintln!("Unable to read X resources: {}", e);
+                return;
+            },
+        };
+
+        let resources = unsafe {
+            let raw = xlib::XResourceManagerString(display);
+            if raw.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(raw).to_string_lossy().into_owned()
+            }
+        };
+        close_display(display);
+
+        for line in resources.lines() {
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let key = key.trim().trim_start_matches('*').trim_start_matches('.');
+            if XRESOURCE_COLORS.contains(&key)
+                && let Some(pixel) = Color::parse_hex(value.trim()) {
+                    self.set(key, pixel);
+                }
+        }
+    }
+}
+
+impl Theme {
+    /// Palette with the colors of the theme
+    pub fn palette(&self) -> Palette {
+        let mut palette = Palette::default();
+        if self.xresources {
+            palette.load_xresources();
+        }
+
+        // entries may refer to the default palette or the X resources
+        let base = palette.clone();
+        for (name, color) in &self.palette {
+            let mut color = color.clone();
+            color.resolve(&base);
+            palette.set(name, color.pixel());
+        }
+        palette
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        let colors = DEFAULT_PALETTE.iter()
+            .map(|(name, pixel)| (name.to_string(), *pixel))
+            .collect();
+        Palette { colors }
+    }
+}
+
+impl From<u64> for Color {
+    fn from(pixel: u64) -> Self {
+        Color::rgb(pixel)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "#{:06x}", self.pixel),
+        }
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawColor {
+            Pixel(u64),
+            Text(String),
+        }
+
+        match RawColor::deserialize(deserializer)? {
+            RawColor::Pixel(pixel) => Ok(Color::rgb(pixel)),
+            RawColor::Text(text) if text.starts_with('#') => match Color::parse_hex(&text) {
+                Some(pixel) => Ok(Color::rgb(pixel)),
+                None => Err(serde::de::Error::custom(format!("invalid color '{}'", text))),
+            },
+            RawColor::Text(name) => match CURRENT_PALETTE.with_borrow(|p| p.get(&name)) {
+                Some(pixel) => Ok(Color { name: Some(name), pixel }),
+                None => Err(serde::de::Error::custom(format!("unknown color '{}'", name))),
+            },
+        }
+    }
+}
@@ -32,6 +32,9 @@ pub struct Workspace { index: u32 }
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, clap::Args)]
 pub struct Status { text: String }
 
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, clap::Args)]
+pub struct WorkspaceName { index: u32, name: String }
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, clap::Args)]
 pub struct ModifierShim {
     #[clap(value_enum)]
@@ -43,6 +46,9 @@ pub enum Command {
     /// Activate window
     Activate,
 
+    /// Add a workspace after the last one
+    AddWorkspace,
+
     /// Center window on screen
     Center,
 
@@ -52,12 +58,21 @@ pub enum Command {
     /// Fullscreen setting of a window
     Fullscreen(ModifierShim),
 
+    /// Insert a workspace at the given index
+    InsertWorkspace(Workspace),
+
     /// Show quick menu
     Menu,
 
     /// Pin window so it is visible on all workspaces
     Pinned(ModifierShim),
 
+    /// Remove workspace (its windows are moved to a neighboring workspace)
+    RemoveWorkspace(Workspace),
+
+    /// Rename workspace
+    RenameWorkspace(WorkspaceName),
+
     /// Send window to workspace
     SendToWorkspace(Workspace),
 
@@ -127,11 +142,16 @@ impl Command {
         } else {
             let result = match self {
                 Command::Activate => controller.activate_window(window_result?),
+                Command::AddWorkspace => controller.count_workspaces()
+                    .and_then(|count| controller.insert_workspace(count)),
                 Command::Center => controller.center_window(window_result?),
                 Command::Close => controller.close_window(window_result?),
                 Command::Fullscreen(mode) => handle_window_setting(C::window_is_fullscreen, C::fullscreen_window, controller,
                                                                    window_result?, *mode),
+                Command::InsertWorkspace(ws) => controller.insert_workspace(ws.index),
                 Command::Pinned(mode) => handle_window_setting(C::window_is_pinned, C::pin_window, controller, window_result?, *mode),
+                Command::RemoveWorkspace(ws) => controller.remove_workspace(ws.index),
+                Command::RenameWorkspace(ws) => controller.rename_workspace(ws.index, ws.name.clone()),
                 Command::SendToWorkspace(ws) => controller.send_window_to_workspace(window_result?, ws.index),
                 Command::SetStatus(status) => controller.set_status(status.text.to_owned()),
                 Command::SwitchWorkspace(ws) => controller.switch_workspace(ws.index),
@@ -1,6 +1,8 @@
 use libmars::platforms::x11::draw::widget::*;
 use libmars::utils::configuration::read_config_file;
 use libmars::utils::configuration::read_file;
+use libmars::utils::configuration::read_palette;
+use libmars::utils::theme::*;
 use serde::{Serialize, Deserialize};
 use std::path;
 use x11::xlib;
@@ -19,7 +21,7 @@ const DEFAULT_TEXT_PADDING_VERT: u32 = 0;
 #[derive(Serialize,Deserialize,PartialEq,Debug,Clone)]
 #[serde(default)]
 pub struct BarStyle {
-    pub background: u64,
+    pub background: Color,
     pub expand_workspace_widgets: bool,
     pub height: u32,
     pub font: String,
@@ -31,17 +33,17 @@ pub struct BarStyle {
 #[derive(Serialize,Deserialize,PartialEq,Debug,Clone)]
 #[serde(default)]
 pub struct TextWidgetStyle {
-    pub foreground: u64,
-    pub background: u64,
+    pub foreground: Color,
+    pub background: Color,
     pub padding_horz: u32,
     pub padding_vert: u32,
 }
 
 #[derive(Serialize,Deserialize,PartialEq,Debug,Clone)]
 pub struct ContainerWidgetStyle {
-    pub foreground: u64,
-    pub inner_background: u64,
-    pub outer_background: u64,
+    pub foreground: Color,
+    pub inner_background: Color,
+    pub outer_background: Color,
     pub padding_horz: u32,
     pub padding_vert: u32,
     pub text_padding_horz: u32,
@@ -61,7 +63,7 @@ pub struct Configuration {
 impl Default for BarStyle {
     fn default() -> Self {
         BarStyle {
-            background: 0x262626,
+            background: Color::named("background"),
             expand_workspace_widgets: false,
             height: 31,
             font: DEFAULT_FONT.to_owned(),
@@ -72,11 +74,21 @@ impl Default for BarStyle {
     }
 }
 
+impl BarStyle {
+    /// Look up named colors in the palette
+    pub fn apply_palette(&mut self, palette: &Palette) {
+        self.background.resolve(palette);
+        self.workspaces.apply_palette(palette);
+        self.title.apply_palette(palette);
+        self.status.apply_palette(palette);
+    }
+}
+
 impl Default for TextWidgetStyle {
     fn default() -> Self {
         TextWidgetStyle {
-            foreground: 0xbcbcbc,
-            background: 0x262626,
+            foreground: Color::named("foreground"),
+            background: Color::named("background"),
             padding_horz: DEFAULT_TEXT_PADDING_HORZ,
             padding_vert: DEFAULT_TEXT_PADDING_VERT
         }
@@ -84,32 +96,38 @@ impl Default for TextWidgetStyle {
 }
 
 impl ContainerWidgetStyle {
+    pub fn apply_palette(&mut self, palette: &Palette) {
+        self.foreground.resolve(palette);
+        self.inner_background.resolve(palette);
+        self.outer_background.resolve(palette);
+    }
+
     pub fn create_flow_layout_widget<W: Widget>(&self, display: *mut xlib::Display, parent: xlib::Window)
             -> Result<X11FlowLayoutWidget<W>, String> {
         let params = X11WidgetParams::new(0, 0, self.padding_horz, self.padding_vert);
-        X11FlowLayoutWidget::new(display, parent, params, self.spacing, Vec::new(), self.outer_background)
+        X11FlowLayoutWidget::new(display, parent, params, self.spacing, Vec::new(), self.outer_background.pixel())
             .map_err(|e| e.to_string())
     }
 
     pub fn create_text_widget(&self, display: *mut xlib::Display, parent: xlib::Window, font: &str)
             -> Result<X11TextWidget, String> {
         let params = X11WidgetParams::new(0, 0, self.text_padding_horz, self.text_padding_vert);
-        X11TextWidget::new(display, parent, params, "".to_string(), font, self.foreground, self.inner_background)
+        X11TextWidget::new(display, parent, params, "".to_string(), font, self.foreground.pixel(), self.inner_background.pixel())
             .map_err(|e| e.to_string())
     }
 
     pub fn create_systray_widget(&self, display: *mut xlib::Display, parent: xlib::Window, parent_height: u32)
             -> Result<SystemTrayWidget, String> {
         let params = X11WidgetParams::new(0, 0, self.padding_horz, self.padding_vert);
-        SystemTrayWidget::new(display, parent, params, parent_height - 2 * self.spacing, self.padding_horz, self.inner_background)
+        SystemTrayWidget::new(display, parent, params, parent_height - 2 * self.spacing, self.padding_horz, self.inner_background.pixel())
             .map_err(|e| e.to_string())
     }
 
     fn default_status() -> Self {
         ContainerWidgetStyle {
-            foreground: 0x262626,
-            inner_background: 0xaf5f5f,
-            outer_background: 0x262626,
+            foreground: Color::named("background"),
+            inner_background: Color::named("color1"),
+            outer_background: Color::named("background"),
             padding_horz: 4,
             padding_vert: 4,
             text_padding_horz: 5,
@@ -120,9 +138,9 @@ impl ContainerWidgetStyle {
 
     fn default_workspaces() -> Self {
         ContainerWidgetStyle {
-            foreground: 0x262626,
-            inner_background: 0x5f87af,
-            outer_background: 0x262626,
+            foreground: Color::named("background"),
+            inner_background: Color::named("color4"),
+            outer_background: Color::named("background"),
             padding_horz: 0,
             padding_vert: 0,
             text_padding_horz: 10,
@@ -133,16 +151,24 @@ impl ContainerWidgetStyle {
 }
 
 impl TextWidgetStyle {
+    pub fn apply_palette(&mut self, palette: &Palette) {
+        self.foreground.resolve(palette);
+        self.background.resolve(palette);
+    }
+
     pub fn create_text_widget(&self, display: *mut xlib::Display, parent: xlib::Window, font: &str)
             -> Result<X11TextWidget, String> {
         let params = X11WidgetParams::new(0, 0, self.padding_horz, self.padding_horz);
-        X11TextWidget::new(display, parent, params, "".to_string(), font, self.foreground, self.background)
+        X11TextWidget::new(display, parent, params, "".to_string(), font, self.foreground.pixel(), self.background.pixel())
             .map_err(|e| e.to_string())
     }
 }
 
 
 pub fn read_config(overwrite_path: Option<path::PathBuf>) -> Configuration {
+    // named colors are looked up in the palette of the theme
+    read_palette();
+
     let result = if let Some(path) = overwrite_path {
         read_file(&path)
     } else {
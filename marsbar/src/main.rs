@@ -11,6 +11,7 @@ use libmars::interfaces::draw::*;
 use libmars::platforms::x11::draw::widget::*;
 use libmars::platforms::x11::draw::canvas::*;
 use libmars::utils::configuration::print_config;
+use libmars::utils::configuration::read_palette;
 use std::ffi::*;
 use std::iter;
 use std::mem::MaybeUninit;
@@ -84,8 +85,8 @@ impl Bar {
         // TODO destroy window on failure
         let mut canvas = X11Canvas::new_for_window(display, window)
             .inspect_err(|_| unsafe { xlib::XDestroyWindow(display, window); })?;
-        canvas.set_foreground(config.style.background)
-            .and(canvas.set_background(config.style.background))
+        canvas.set_foreground(config.style.background.pixel())
+            .and(canvas.set_background(config.style.background.pixel()))
             .and(canvas.set_font(&config.style.font))
             .inspect_err(|_| unsafe { xlib::XDestroyWindow(display, window); })?;
 
@@ -117,7 +118,7 @@ impl Bar {
     fn arrange(&mut self) {
         // arrange title segment
         self.active_window = self.get_active_window();
-        self.arrange_title(self.get_active_window_title());
+        self.arrange_title(self.get_title());
 
         // arrange desktop segment
         let result = self.root.x11_get_text_list_property(self.display, NetDesktopNames);
@@ -277,7 +278,7 @@ impl Bar {
             let height = self.dimensions.h();
             let x = (self.dimensions.x() + self.dimensions.w() as i32) - width as i32;
             let y = self.dimensions.y();
-            self.canvas.fill_rectangle_with(x, y, width, height, self.config.style.status.outer_background);
+            self.canvas.fill_rectangle_with(x, y, width, height, self.config.style.status.outer_background.pixel());
         }
 
         self.canvas.flush();
@@ -322,6 +323,29 @@ impl Bar {
         }
     }
 
+    fn get_root_text(&self, property: X11Atom) -> String {
+        self.root.x11_get_text_list_property(self.display, property).ok()
+            .and_then(|list| list.into_iter().next())
+            .unwrap_or_default()
+    }
+
+    /// Title of the active window or the pending key sequence, prefixed by the binding mode
+    fn get_title(&self) -> String {
+        let prefix = self.get_root_text(MarsKeyPrefix);
+        let title = if prefix.is_empty() {
+            self.get_active_window_title()
+        } else {
+            format!("{} ...", prefix)
+        };
+
+        let mode = self.get_root_text(MarsMode);
+        if mode.is_empty() {
+            title
+        } else {
+            format!("[{}] {}", mode, title)
+        }
+    }
+
     fn handle_xevent(&mut self, event: xlib::XEvent) {
         unsafe {
             if event.any.window == self.root {
@@ -331,7 +355,7 @@ impl Bar {
             } else if Some(event.any.window) == self.active_window
                     && event.get_type() == xlib::PropertyNotify
                     && event.property.atom == WMName.to_xlib_atom(self.display) {
-                let title = self.get_active_window_title();
+                let title = self.get_title();
                 self.arrange_title(title);
             } else if event.get_type() == xlib::ClientMessage
                     && Some(event.any.window) == self.systray.as_ref().map(|w| w.wid())
@@ -387,7 +411,7 @@ impl Bar {
                             }
                         }
 
-                        let title = self.get_active_window_title();
+                        let title = self.get_title();
                         self.arrange_title(title);
                     },
                     NetCurrentDesktop => {
@@ -408,17 +432,46 @@ impl Bar {
                         };
                         self.arrange_workspaces(workspace_names);
                     }
+                    MarsKeyPrefix | MarsMode => {
+                        let title = self.get_title();
+                        self.arrange_title(title);
+                    },
                     MarsStatus | WMName => {
                         if let Ok(status) = self.get_status() {
                             self.arrange_status(status)
                         }
                     },
+                    MarsReloadTheme | ResourceManager => self.reload_theme(),
                     _ => (),
                 }
             };
         }
     }
 
+    fn reload_theme(&mut self) {
+        let style = &mut self.config.style;
+        style.apply_palette(&read_palette());
+
+        let _ = self.canvas.set_foreground(style.background.pixel())
+            .and(self.canvas.set_background(style.background.pixel()));
+        let _ = self.workspace_widget.set_background(style.workspaces.outer_background.pixel());
+        let _ = self.status_widget.set_background(style.status.outer_background.pixel());
+        for widget in self.status_widget.children_mut() {
+            let _ = widget.set_foreground(style.status.foreground.pixel())
+                .and(widget.set_background(style.status.inner_background.pixel()));
+        }
+        let _ = self.title_widget.set_foreground(style.title.foreground.pixel())
+            .and(self.title_widget.set_background(style.title.background.pixel()));
+        if let Some(systray) = &mut self.systray {
+            let _ = systray.set_background(style.status.inner_background.pixel());
+        }
+
+        if let Ok(idx) = self.get_active_workspace() {
+            self.set_active_workspace(idx);
+        }
+        self.draw();
+    }
+
     fn reconfigure(&mut self, monitor_config: MonitorConfig) {
         self.dimensions.set_pos(monitor_config.dimensions().pos());
         self.dimensions.set_w(monitor_config.dimensions().w());
@@ -449,11 +502,11 @@ impl Bar {
     fn set_active_workspace(&mut self, new_idx: u32) {
         for (i, widget) in &mut self.workspace_widget.children_mut().enumerate() {
             if i as u32 == new_idx {
-                widget.set_foreground(self.config.style.workspaces.inner_background).unwrap();
-                widget.set_background(self.config.style.workspaces.foreground).unwrap();
+                widget.set_foreground(self.config.style.workspaces.inner_background.pixel()).unwrap();
+                widget.set_background(self.config.style.workspaces.foreground.pixel()).unwrap();
             } else {
-                widget.set_foreground(self.config.style.workspaces.foreground).unwrap();
-                widget.set_background(self.config.style.workspaces.inner_background).unwrap();
+                widget.set_foreground(self.config.style.workspaces.foreground.pixel()).unwrap();
+                widget.set_background(self.config.style.workspaces.inner_background.pixel()).unwrap();
             }
         }
     }
@@ -197,6 +197,21 @@ impl SystemTrayWidget {
         self.canvas.match_resize();
     }
 
+    pub fn set_background(&mut self, color: u64) -> Result<(), String> {
+        self.canvas.set_foreground(color)?;
+        self.canvas.set_background(color)?;
+        self.bg_color = color;
+
+        for tray_icon in &self.tray_icons {
+            unsafe {
+                xlib::XSetWindowBackground(self.display, *tray_icon, color);
+                xlib::XClearWindow(self.display, *tray_icon);
+            }
+        }
+        self.redraw();
+        Ok(())
+    }
+
     pub fn show(&mut self) {
         if !self.is_visible {
             unsafe {
@@ -1,13 +1,33 @@
 use libmars::common::Dimensions;
 
+/// Smallest weight a client can have in a stack
+pub const MIN_CLIENT_WEIGHT: f32 = 0.25;
+/// Largest weight a client can have in a stack
+pub const MAX_CLIENT_WEIGHT: f32 = 4.0;
+
 #[derive(PartialEq)]
-#[derive(Default)]
 pub struct Attributes {
     pub is_floating: bool,
     pub is_moving: bool,
     pub is_pinned: bool,
 
     pub floating_dimensions: Option<Dimensions>,
-}
+    /// geometry of a floating client before it was snapped to a zone or side
+    pub unsnapped_dimensions: Option<Dimensions>,
 
+    /// share of the client relative to the other clients in the same stack
+    pub weight: f32,
+}
 
+impl Default for Attributes {
+    fn default() -> Self {
+        Attributes {
+            is_floating: false,
+            is_moving: false,
+            is_pinned: false,
+            floating_dimensions: None,
+            unsnapped_dimensions: None,
+            weight: 1.0,
+        }
+    }
+}
@@ -4,6 +4,7 @@ use libmars::platforms::x11::misc::get_keysym;
 use serde::{Serialize, Deserialize};
 use std::cell::RefCell;
 use std::cmp;
+use std::fmt;
 use std::rc::Rc;
 use x11::xlib::{Mod1Mask, Mod4Mask, ShiftMask, ControlMask};
 
@@ -35,10 +36,16 @@ macro_rules! frame_button_binding {
 // #[serde(tag = "action", content = "arg")]
 // #[serde(tag = "type")]
 pub enum BindingAction {
+    /// Add a workspace after the last workspace of the current monitor
+    AppendWorkspace,
     /// Center the current client on the screen
     CenterClient,
+    /// Change the weight of the client relative to the other clients in its stack
+    ChangeClientWeight(f32),
     /// Change the ration between main and stack area
     ChangeMainRatio(f32),
+    /// Change the share of the client in its split for the bsp layout (changes the main ratio for other layouts)
+    ChangeSplitRatio(f32),
     /// Close the client
     CloseClient,
     /// Cycle through clients
@@ -49,52 +56,99 @@ pub enum BindingAction {
     CycleMonitor(i32),
     /// Cycle through workspaces
     CycleWorkspace(i32),
+    /// Activate the bindings of a binding mode (grabs the keyboard)
+    EnterMode(String),
     /// Execute a command in the system shell
     Execute(String),
     /// Exit the window manager
     Exit,
-    /// Focus next client in the specified direction
+    /// Return from a binding mode to the default bindings
+    ExitMode,
+    /// Mirror the split containing the client (bsp layout)
+    FlipSplit,
+    /// Focus next client in the specified direction (continues on the adjacent monitor)
     FocusDirection(Direction),
+    /// Walk through the focus history (most recently used first) across workspaces and monitors
+    FocusHistory(i32),
+    /// Switch back to the previously focused client
+    FocusLast,
+    /// Focus the adjacent monitor in the specified direction
+    FocusMonitor(Direction),
     /// Switch between the last focused window of the main and stack area
     FocusMain,
-    /// Increase or decrease the gap width of the current workspace
+    /// Increase or decrease the inner and outer gaps of the current workspace
     IncGaps(i32),
     /// Increase or decrease the number of clients in the main area
     IncNMain(i32),
+    /// Add a workspace in front of the current workspace
+    InsertWorkspace,
+    /// Hide the client until it is activated again
+    MinimizeClient,
     /// Move the client with the mouse
     MouseMove,
     /// Place a window with the mouse
     MousePlace,
-    /// Resize a window with the mouse
+    /// Resize a window with the mouse (moves the border to the adjacent window for tiled windows)
     MouseResize,
     /// Resize a window around its center
     MouseResizeCentered,
+    /// Move a floating client by the given distance (swaps tiled clients in that direction instead)
+    MoveFloating(Length, Length),
     /// Move client to/from the main area
     MoveMain,
     /// Move the client to a different monitor (relative to the current monitor)
     MoveMonitor(i32),
+    /// Move the client to the adjacent monitor in the specified direction
+    MoveToMonitor(Direction),
     /// Move the client to a different workspace
-    MoveWorkspace(u32),
+    MoveWorkspace(WorkspaceSelector),
+    /// Move the current workspace to a different monitor (relative to the current monitor)
+    MoveWorkspaceToMonitor(i32),
+    /// Choose where the next window is inserted next to the client (bsp layout, repeat to cancel)
+    Preselect(Direction),
     /// Switch to the previously focused workspace
     PreviousWorkspace,
+    /// Reload the colors of the shared theme
+    ReloadTheme,
+    /// Remove the current workspace (its clients are moved to the previous workspace)
+    RemoveWorkspace,
+    /// Rename the current workspace
+    RenameWorkspace(String),
+    /// Resize a floating client by the given amount (changes the main ratio for tiled clients)
+    ResizeFloating(Length, Length),
+    /// Resize a floating client to the given size while keeping its center
+    ResizeFloatingTo(Length, Length),
     /// Restart the window manager
     Restart,
+    /// Restore the most recently minimized client of the current workspace
+    RestoreMinimized,
+    /// Rotate the split containing the client clockwise (bsp layout)
+    RotateSplit,
     /// Set the layout
     SetLayout(LayoutType),
     /// Set the stack mode for the dynamic layout
     SetStackMode(StackMode),
     /// Set the stack position for the dynamic layout
     SetStackPosition(StackPosition),
+    /// Move a floating client to the edge of the window area (swaps tiled clients in that direction instead)
+    SnapToEdge(Direction),
+    /// Snap a floating client to a side of the window area (left/right cycle through 1/2, 2/3 and 1/3 of the
+    /// width, up maximizes, down restores the previous geometry)
+    SnapWindow(Direction),
     /// Move the client up or down the stack
     StackMove(i32),
     /// Swap with next client in direction
     SwapDirection(Direction),
     /// Switch to a different workspace
-    SwitchWorkspace(u32),
+    SwitchWorkspace(WorkspaceSelector),
     /// Toggle floating state on the window
     ToggleFloating,
     /// Toggle fullscreen state on the window
     ToggleFullscreen,
+    /// Toggle pinned state on the window (pinned windows are shown on all workspaces)
+    TogglePinned,
+    /// Open the window switcher or move its selection (releasing the modifiers activates the selection)
+    WindowSwitcher(i32),
 }
 
 #[derive(Serialize,Deserialize,Clone,Debug,PartialEq,Eq)]
@@ -113,6 +167,29 @@ pub enum Direction {
     Right,
 }
 
+/// Distance or size either in pixels (`20`) or relative to the size of the monitor (`"50%"`)
+#[derive(Serialize,Deserialize,PartialEq,Debug,Copy,Clone)]
+#[serde(try_from = "LengthRepr", into = "LengthRepr")]
+pub enum Length {
+    Pixels(i32),
+    Percent(f32),
+}
+
+#[derive(Serialize,Deserialize)]
+#[serde(untagged)]
+enum LengthRepr {
+    Pixels(i32),
+    Text(String),
+}
+
+/// Workspace either selected by its index on the monitor (`0`) or by its name (`"www"`)
+#[derive(Serialize,Deserialize,PartialEq,Eq,Debug,Clone)]
+#[serde(untagged)]
+pub enum WorkspaceSelector {
+    Index(u32),
+    Name(String),
+}
+
 #[derive(Serialize,Deserialize,PartialEq,Debug,Clone)]
 pub struct KeyBinding {
     /// list of modifiers that apply to this binding
@@ -124,10 +201,28 @@ pub struct KeyBinding {
     /// the leading "XK_")
     key: String,
 
+    /// keys that have to be pressed after the first one to trigger the action
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sequence: Vec<KeyChord>,
+
+    /// binding mode the binding belongs to (none for the default bindings)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+
     /// action to execute on key press
     action: BindingAction,
 }
 
+#[derive(Serialize,Deserialize,PartialEq,Debug,Clone)]
+pub struct KeyChord {
+    /// list of modifiers that apply to this key
+    #[serde(default)]
+    modifiers: Vec<Modifier>,
+
+    /// key name (see [KeyBinding])
+    key: String,
+}
+
 #[derive(Serialize,Deserialize,PartialEq,Debug,Clone)]
 pub struct ButtonBinding {
     /// list of modifiers that apply to this binding
@@ -139,6 +234,10 @@ pub struct ButtonBinding {
     /// button target
     targets: Vec<ButtonTarget>,
 
+    /// only trigger on the second click of a double click
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    double_click: bool,
+
     /// action to execute on key press
     action: BindingAction,
 }
@@ -148,17 +247,27 @@ impl BindingAction {
                                          client_option: Option<Rc<RefCell<B::Client>>>) {
         use BindingAction::*;
         match self {
+            AppendWorkspace => wm.add_workspace(backend, false),
             CenterClient => if let Some(client_rc) = client_option {
                 wm.center_client(backend, client_rc);
             },
-            ChangeMainRatio(f) =>  wm.current_workspace_mut(backend).change_main_ratio(*f),
+            ChangeClientWeight(f) => if let Some(client_rc) = client_option
+                && let Some(ws) = wm.get_workspace_mut(&client_rc) {
+                    ws.change_client_weight(&client_rc, *f);
+                },
+            ChangeMainRatio(f) =>  wm.current_workspace_mut().change_main_ratio(*f),
+            ChangeSplitRatio(f) => if let Some(client_rc) = client_option
+                && let Some(ws) = wm.get_workspace_mut(&client_rc) {
+                    ws.change_split_ratio(&client_rc, *f);
+                },
             CloseClient => if let Some(client_rc) = client_option {
                 client_rc.borrow().close();
             },
             CycleClient(inc) => wm.cycle_client(backend, *inc),
-            CycleLayout => wm.current_workspace_mut(backend).cycle_layout(),
+            CycleLayout => wm.cycle_layout(),
             CycleMonitor(inc) => wm.cycle_monitor(backend, *inc),
             CycleWorkspace(inc) => wm.cycle_workspace(backend, *inc),
+            EnterMode(mode) => wm.enter_mode(backend, mode),
             Execute(cmd) => {
                 if let Ok(mut handle) = std::process::Command::new("sh").arg("-c").arg(cmd).spawn() {
                     std::thread::spawn(move || {
@@ -169,19 +278,35 @@ impl BindingAction {
             Exit => {
                 wm.exit(backend);
             },
+            ExitMode => wm.exit_mode(backend),
+            FlipSplit => if let Some(client_rc) = client_option
+                && let Some(ws) = wm.get_workspace_mut(&client_rc) {
+                    ws.flip_split(&client_rc);
+                },
             FocusDirection(d) => wm.focus_direction(backend, *d),
+            FocusHistory(inc) => wm.cycle_focus_history(backend, *inc),
+            FocusLast => wm.focus_last(backend),
+            FocusMonitor(dir) => wm.focus_monitor(backend, *dir),
             FocusMain => wm.switch_to_main(backend),
-            IncGaps(i) => wm.current_workspace_mut(backend).inc_gaps(*i),
-            IncNMain(i) => wm.current_workspace_mut(backend).inc_nmain(*i),
+            IncGaps(i) => wm.current_workspace_mut().inc_gaps(*i),
+            IncNMain(i) => wm.current_workspace_mut().inc_nmain(*i),
+            InsertWorkspace => wm.add_workspace(backend, true),
+            MinimizeClient => if let Some(client_rc) = client_option {
+                wm.minimize_client(backend, client_rc);
+            },
             MouseMove => if let Some(client_rc) = client_option {
-                backend.mouse_move(wm, client_rc);
-                wm.current_monitor_mut(backend).restack_current();
+                wm.mouse_move(backend, client_rc);
+                wm.current_monitor_mut().restack_current();
             },
             MousePlace => if let Some(client_rc) = client_option {
                 wm.mouse_place(backend, client_rc);
             },
-            MouseResize => if let Some(client_rc) = client_option && is_floating!(wm, &client_rc) {
-                backend.mouse_resize(wm, client_rc);
+            MouseResize => if let Some(client_rc) = client_option {
+                if is_floating!(wm, &client_rc) {
+                    backend.mouse_resize(wm, client_rc);
+                } else {
+                    wm.mouse_resize_tiled(backend, client_rc);
+                }
             },
             MouseResizeCentered => if let Some(client_rc) = client_option && is_floating!(wm, &client_rc) {
                 wm.mouse_resize_centered(backend, client_rc);
@@ -189,29 +314,70 @@ impl BindingAction {
             MoveMonitor(inc) => if let Some(client_rc) = client_option {
                 wm.move_client_to_monitor(client_rc, *inc);
             },
+            MoveToMonitor(dir) => if let Some(client_rc) = client_option {
+                wm.move_client_to_monitor_in_direction(client_rc, *dir);
+            },
             MoveWorkspace(ws) => if let Some(client_rc) = client_option {
-                let ws_index_option = wm.get_monitor_mut(&client_rc)
-                    .and_then(|m| m.workspace(*ws))
-                    .map(|ws| ws.global_index());
+                let ws_index_option = wm.get_monitor(&client_rc)
+                    .and_then(|m| wm.select_workspace(m, ws));
                 if let Some(ws_index) = ws_index_option {
                     wm.move_to_workspace(backend, client_rc, ws_index);
                 }
             },
+            MoveWorkspaceToMonitor(inc) => wm.move_workspace_to_monitor(backend, *inc),
+            Preselect(dir) => if let Some(client_rc) = client_option
+                && let Some(ws) = wm.get_workspace_mut(&client_rc) {
+                    ws.preselect(&client_rc, *dir);
+                },
             PreviousWorkspace => wm.switch_prev_workspace(backend),
+            ReloadTheme => {
+                wm.reload_theme(backend);
+                backend.export_theme_change();
+            },
+            RemoveWorkspace => {
+                let ws_index = wm.active_workspace(backend);
+                wm.remove_workspace(backend, ws_index);
+            },
+            RenameWorkspace(name) => {
+                let ws_index = wm.active_workspace(backend);
+                wm.rename_workspace(backend, ws_index, name.clone());
+            },
+            MoveFloating(dx, dy) => if let Some(client_rc) = client_option {
+                wm.move_floating(backend, client_rc, *dx, *dy);
+            },
             MoveMain => if let Some(client_rc) = client_option {
-                wm.current_workspace_mut(backend).move_main(client_rc);
+                wm.current_workspace_mut().move_main(client_rc);
+            },
+            ResizeFloating(dw, dh) => if let Some(client_rc) = client_option {
+                wm.resize_floating(client_rc, *dw, *dh);
+            },
+            ResizeFloatingTo(w, h) => if let Some(client_rc) = client_option {
+                wm.resize_floating_to(client_rc, *w, *h);
             },
             Restart => wm.restart(backend),
-            SetLayout(layout) => wm.current_workspace_mut(backend).set_layout(*layout),
-            SetStackMode(mode) => wm.current_workspace_mut(backend).set_stack_mode(*mode),
-            SetStackPosition(position) => wm.current_workspace_mut(backend).set_stack_position(*position),
+            RestoreMinimized => if let Some(client_rc) = wm.current_workspace().last_minimized() {
+                wm.activate_client(backend, client_rc);
+            },
+            RotateSplit => if let Some(client_rc) = client_option
+                && let Some(ws) = wm.get_workspace_mut(&client_rc) {
+                    ws.rotate_split(&client_rc);
+                },
+            SetLayout(layout) => wm.set_layout(layout.clone()),
+            SetStackMode(mode) => wm.current_workspace_mut().set_stack_mode(*mode),
+            SetStackPosition(position) => wm.current_workspace_mut().set_stack_position(*position),
+            SnapToEdge(dir) => if let Some(client_rc) = client_option {
+                wm.snap_to_edge(backend, client_rc, *dir);
+            },
+            SnapWindow(dir) => if let Some(client_rc) = client_option {
+                wm.snap_window(backend, client_rc, *dir);
+            },
             StackMove(i) => if let Some(client_rc) = client_option {
-                wm.current_workspace_mut(backend).stack_move(client_rc, *i);
+                wm.current_workspace_mut().stack_move(client_rc.clone(), *i);
+                wm.select_client(backend, client_rc);
             },
             SwapDirection(dir) => wm.swap_direction(backend, *dir),
             SwitchWorkspace(ws) => {
-                let ws_index_option = wm.current_monitor(backend).workspace(*ws)
-                    .map(|ws| ws.global_index());
+                let ws_index_option = wm.select_workspace(wm.current_monitor(), ws);
                 if let Some(ws_index) = ws_index_option {
                     wm.switch_workspace(backend, ws_index);
                 }
@@ -222,21 +388,49 @@ impl BindingAction {
             ToggleFullscreen => if let Some(client_rc) = client_option {
                 wm.toggle_fullscreen_client(backend, client_rc);
             },
+            TogglePinned => if let Some(client_rc) = client_option {
+                let is_pinned = client_rc.borrow().attributes().is_pinned;
+                wm.set_client_pinned(backend, client_rc, !is_pinned);
+            },
+            WindowSwitcher(inc) => wm.window_switcher(backend, *inc),
         }
     }
+
+    /// Whether the action keeps an ongoing walk through the focus history alive
+    pub fn walks_focus_history(&self) -> bool {
+        matches!(self, BindingAction::FocusHistory(_))
+    }
 }
 
 impl KeyBinding {
     pub fn new(modifiers: Vec<Modifier>, key: &str, action: BindingAction) -> Self {
-        KeyBinding { modifiers, key: key.to_owned(), action }
+        KeyBinding { modifiers, key: key.to_owned(), sequence: Vec::new(), mode: None, action }
     }
 
     pub fn action(&self) -> BindingAction {
         self.action.clone()
     }
 
-    pub fn matches(&self, modifiers: u32, key: u32) -> bool {
-        modifiers == self.modifiers() && key == self.key()
+    /// All keys of the binding including the first one
+    pub fn chords(&self) -> Vec<KeyChord> {
+        let first = KeyChord { modifiers: self.modifiers.clone(), key: self.key.clone() };
+        let mut chords = vec![first];
+        chords.extend(self.sequence.iter().cloned());
+        chords
+    }
+
+    /// Check whether the pressed keys trigger the binding
+    pub fn matches(&self, pressed: &[(u32, u32)]) -> bool {
+        pressed.len() == self.sequence.len() + 1 && self.starts_with(pressed)
+    }
+
+    /// Check whether the pressed keys are the beginning of a longer sequence
+    pub fn continues(&self, pressed: &[(u32, u32)]) -> bool {
+        pressed.len() < self.sequence.len() + 1 && self.starts_with(pressed)
+    }
+
+    pub fn mode(&self) -> Option<&str> {
+        self.mode.as_deref()
     }
 
     pub fn modifiers(&self) -> u32 {
@@ -246,11 +440,36 @@ impl KeyBinding {
     pub fn key(&self) -> u32 {
         get_keysym(&self.key) as u32
     }
+
+    fn starts_with(&self, pressed: &[(u32, u32)]) -> bool {
+        self.chords().iter().zip(pressed)
+            .all(|(chord, (modifiers, key))| chord.matches(*modifiers, *key))
+    }
+}
+
+impl KeyChord {
+    pub fn matches(&self, modifiers: u32, key: u32) -> bool {
+        modifiers == self.modifiers.iter().fold(0, |a, b| a | b.mask())
+            && key == get_keysym(&self.key) as u32
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{:?}+", modifier)?;
+        }
+        write!(f, "{}", self.key)
+    }
 }
 
 impl ButtonBinding {
     pub fn new(modifiers: Vec<Modifier>, button: u32, targets: Vec<ButtonTarget>, action: BindingAction) -> Self {
-        ButtonBinding { modifiers, button, targets, action }
+        ButtonBinding { modifiers, button, targets, double_click: false, action }
+    }
+
+    pub fn double_click(&self) -> bool {
+        self.double_click
     }
 
     pub fn action(&self) -> BindingAction {
@@ -274,6 +493,69 @@ impl ButtonBinding {
     }
 }
 
+impl Direction {
+    /// Direction of the dominant component of a movement
+    pub fn from_delta(dx: i32, dy: i32) -> Option<Direction> {
+        if dx == 0 && dy == 0 {
+            None
+        } else if dx.abs() >= dy.abs() {
+            Some(if dx > 0 { Direction::Right } else { Direction::Left })
+        } else {
+            Some(if dy > 0 { Direction::Down } else { Direction::Up })
+        }
+    }
+
+    /// Distance to a point that lies in this direction (distance along the axis plus the offset perpendicular to it)
+    pub fn distance(&self, from: (i32, i32), to: (i32, i32)) -> Option<u32> {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let (along, across) = match self {
+            Direction::Up => (-dy, dx),
+            Direction::Down => (dy, dx),
+            Direction::Left => (-dx, dy),
+            Direction::Right => (dx, dy),
+        };
+
+        if along > 0 && along >= across.abs() {
+            Some(along as u32 + across.unsigned_abs())
+        } else {
+            None
+        }
+    }
+}
+
+impl Length {
+    /// Convert to pixels (percentages are relative to `total`)
+    pub fn resolve(&self, total: u32) -> i32 {
+        match self {
+            Length::Pixels(pixels) => *pixels,
+            Length::Percent(percent) => (total as f32 * percent / 100.0).round() as i32,
+        }
+    }
+}
+
+impl TryFrom<LengthRepr> for Length {
+    type Error = String;
+
+    fn try_from(value: LengthRepr) -> Result<Self, Self::Error> {
+        match value {
+            LengthRepr::Pixels(pixels) => Ok(Length::Pixels(pixels)),
+            LengthRepr::Text(text) => text.trim().strip_suffix('%')
+                .and_then(|p| p.trim().parse().ok())
+                .map(Length::Percent)
+                .ok_or(format!("invalid length '{}' (expected pixels or percentage)", text)),
+        }
+    }
+}
+
+impl From<Length> for LengthRepr {
+    fn from(value: Length) -> Self {
+        match value {
+            Length::Pixels(pixels) => LengthRepr::Pixels(pixels),
+            Length::Percent(percent) => LengthRepr::Text(format!("{}%", percent)),
+        }
+    }
+}
+
 impl Modifier {
     pub fn mask(&self) -> u32 {
         match self {
@@ -311,12 +593,22 @@ pub fn default_key_bindings(nworkspaces: u32) -> Vec<KeyBinding> {
         KeyBinding::new(vec![DEFAULT_MODKEY, Shift], "k", SwapDirection(Up)),
         KeyBinding::new(vec![DEFAULT_MODKEY, Shift], "h", SwapDirection(Left)),
         KeyBinding::new(vec![DEFAULT_MODKEY, Shift], "l", SwapDirection(Right)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control], "j", FocusMonitor(Down)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control], "k", FocusMonitor(Up)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control], "h", FocusMonitor(Left)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control], "l", FocusMonitor(Right)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control, Shift], "j", MoveToMonitor(Down)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control, Shift], "k", MoveToMonitor(Up)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control, Shift], "h", MoveToMonitor(Left)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control, Shift], "l", MoveToMonitor(Right)),
         KeyBinding::new(vec![DEFAULT_MODKEY], "period", CycleWorkspace(1)),
         KeyBinding::new(vec![DEFAULT_MODKEY], "comma", CycleWorkspace(-1)),
         KeyBinding::new(vec![DEFAULT_MODKEY], "f", ToggleFullscreen),
         KeyBinding::new(vec![DEFAULT_MODKEY, Shift], "space", ToggleFloating),
         KeyBinding::new(vec![DEFAULT_MODKEY], "z", CenterClient),
         KeyBinding::new(vec![DEFAULT_MODKEY], "Tab", PreviousWorkspace),
+        KeyBinding::new(vec![DEFAULT_MODKEY], "grave", WindowSwitcher(1)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Shift], "grave", WindowSwitcher(-1)),
         KeyBinding::new(vec![DEFAULT_MODKEY], "Return", Execute("$TERMINAL".to_owned())),
         KeyBinding::new(vec![DEFAULT_MODKEY], "d", Execute("rofi -show drun".to_owned())),
         KeyBinding::new(vec![DEFAULT_MODKEY, Control], "BackSpace", Restart),
@@ -330,12 +622,14 @@ pub fn default_key_bindings(nworkspaces: u32) -> Vec<KeyBinding> {
         KeyBinding::new(vec![DEFAULT_MODKEY], "Prior", CycleMonitor(-1)),
         KeyBinding::new(vec![DEFAULT_MODKEY, Shift], "Next", MoveMonitor(1)),
         KeyBinding::new(vec![DEFAULT_MODKEY, Shift], "Prior", MoveMonitor(-1)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control], "Next", MoveWorkspaceToMonitor(1)),
+        KeyBinding::new(vec![DEFAULT_MODKEY, Control], "Prior", MoveWorkspaceToMonitor(-1)),
     ];
 
     for i in 0..cmp::min(nworkspaces, 9) {
         let key_name = format!("{}", i + 1);
-        bindings.push(KeyBinding::new(vec!(DEFAULT_MODKEY), &key_name, SwitchWorkspace(i)));
-        bindings.push(KeyBinding::new(vec!(DEFAULT_MODKEY, Modifier::Shift), &key_name, MoveWorkspace(i)));
+        bindings.push(KeyBinding::new(vec!(DEFAULT_MODKEY), &key_name, SwitchWorkspace(WorkspaceSelector::Index(i))));
+        bindings.push(KeyBinding::new(vec!(DEFAULT_MODKEY, Modifier::Shift), &key_name, MoveWorkspace(WorkspaceSelector::Index(i))));
     }
 
     bindings
@@ -0,0 +1,304 @@
+use libmars::common::*;
+use libmars::interfaces::wm::Client;
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+
+use crate::attributes::*;
+use crate::bindings::Direction;
+use crate::layouts::{inner_area, split_area};
+
+/// Smallest share of a split that a window can be resized to
+const MIN_SPLIT_RATIO: f32 = 0.1;
+
+/// Split tree for manually tiling windows (bsp layout)
+///
+/// Every leaf holds a client, every inner node splits its area between its two children.
+#[derive(PartialEq)]
+pub struct BspTree<C: Client<Attributes>> {
+    root: Option<BspNode<C>>,
+    preselection: Option<Preselection<C>>,
+}
+
+#[derive(PartialEq)]
+enum BspNode<C: Client<Attributes>> {
+    Leaf(Rc<RefCell<C>>),
+    Split(Box<BspSplit<C>>),
+}
+
+#[derive(PartialEq)]
+struct BspSplit<C: Client<Attributes>> {
+    /// whether the children are placed next to each other instead of below each other
+    side_by_side: bool,
+    /// share of the first child
+    ratio: f32,
+    first: BspNode<C>,
+    second: BspNode<C>,
+    /// area that was covered by the split when the layout was last applied
+    area: Dimensions,
+}
+
+/// Where the next window is going to be inserted
+#[derive(PartialEq)]
+struct Preselection<C: Client<Attributes>> {
+    client: Rc<RefCell<C>>,
+    dir: Direction,
+    /// share that the preselected client keeps
+    ratio: f32,
+}
+
+impl<C: Client<Attributes>> BspTree<C> {
+    pub fn new() -> BspTree<C> {
+        BspTree { root: None, preselection: None }
+    }
+
+    /// Lay out all clients of the tree inside `win_area`
+    pub fn apply(&mut self, win_area: Dimensions, outer_gap: (u32, u32, u32, u32), inner_gap: u32) {
+        if let Some(root) = &mut self.root {
+            root.apply(inner_area(win_area, outer_gap), inner_gap);
+        }
+    }
+
+    /// Change the share of the client in its split (or of a pending preselection)
+    pub fn change_ratio(&mut self, client_rc: &Rc<RefCell<C>>, inc: f32) {
+        if let Some(preselection) = &mut self.preselection
+            && preselection.client == *client_rc {
+                preselection.ratio = clamp_ratio(preselection.ratio + inc);
+                return;
+            }
+
+        if let Some(split) = self.parent_of_mut(client_rc) {
+            let inc = if split.first.is_leaf(client_rc) { inc } else { -inc };
+            split.ratio = clamp_ratio(split.ratio + inc);
+        }
+    }
+
+    pub fn contains(&self, client_rc: &Rc<RefCell<C>>) -> bool {
+        self.root.as_ref().map(|r| r.contains(client_rc)).unwrap_or(false)
+    }
+
+    /// Mirror the split containing the client
+    pub fn flip(&mut self, client_rc: &Rc<RefCell<C>>) {
+        if let Some(split) = self.parent_of_mut(client_rc) {
+            mem::swap(&mut split.first, &mut split.second);
+            split.ratio = 1.0 - split.ratio;
+        }
+    }
+
+    /// Insert a client by splitting `target` (falls back to the preselected client)
+    pub fn insert(&mut self, client_rc: Rc<RefCell<C>>, target: Option<&Rc<RefCell<C>>>, split_ratio: f32) {
+        let root = match &mut self.root {
+            Some(root) => root,
+            None => {
+                self.root = Some(BspNode::Leaf(client_rc));
+                return;
+            },
+        };
+
+        // a preselection takes precedence over the target
+        let preselection = self.preselection.take().filter(|p| root.contains(&p.client));
+        let target = preselection.as_ref().map(|p| p.client.clone())
+            .or(target.filter(|t| root.contains(t)).cloned())
+            .unwrap_or_else(|| root.last_leaf());
+        let (dir, ratio) = match preselection {
+            Some(preselection) => (preselection.dir, preselection.ratio),
+            None => {
+                // split along the longer side of the target
+                let (w, h) = target.borrow().size();
+                (if w >= h { Direction::Right } else { Direction::Down }, split_ratio)
+            },
+        };
+
+        if let Some(node) = root.leaf_mut(&target) {
+            let existing = mem::replace(node, BspNode::Leaf(client_rc.clone()));
+            let new = BspNode::Leaf(client_rc);
+            let side_by_side = matches!(dir, Direction::Left | Direction::Right);
+            let (first, second, ratio) = match dir {
+                Direction::Left | Direction::Up => (new, existing, 1.0 - ratio),
+                Direction::Right | Direction::Down => (existing, new, ratio),
+            };
+            *node = BspNode::Split(Box::new(BspSplit {
+                side_by_side, ratio, first, second,
+                area: Dimensions::new(0, 0, 0, 0),
+            }));
+        }
+    }
+
+    /// Preselect where the next window is going to be inserted (preselecting the same direction again cancels it)
+    pub fn preselect(&mut self, client_rc: &Rc<RefCell<C>>, dir: Direction, split_ratio: f32) {
+        if !self.contains(client_rc) {
+            return;
+        }
+
+        let same = self.preselection.as_ref()
+            .map(|p| p.client == *client_rc && p.dir == dir)
+            .unwrap_or(false);
+        self.preselection = if same {
+            None
+        } else {
+            Some(Preselection { client: client_rc.clone(), dir, ratio: split_ratio })
+        };
+    }
+
+    pub fn remove(&mut self, client_rc: &Rc<RefCell<C>>) {
+        if self.preselection.as_ref().map(|p| p.client == *client_rc).unwrap_or(false) {
+            self.preselection = None;
+        }
+
+        let root = match &mut self.root {
+            Some(root) => root,
+            None => return,
+        };
+        if root.is_leaf(client_rc) {
+            self.root = None;
+            return;
+        }
+
+        // replace the parent split with the sibling of the client
+        if let Some(node) = root.parent_node_mut(client_rc)
+            && let BspNode::Split(split) = mem::replace(node, BspNode::Leaf(client_rc.clone())) {
+                let split = *split;
+                *node = if split.first.is_leaf(client_rc) { split.second } else { split.first };
+            }
+    }
+
+    /// Rotate the split containing the client clockwise
+    pub fn rotate(&mut self, client_rc: &Rc<RefCell<C>>) {
+        if let Some(split) = self.parent_of_mut(client_rc) {
+            if !split.side_by_side {
+                mem::swap(&mut split.first, &mut split.second);
+                split.ratio = 1.0 - split.ratio;
+            }
+            split.side_by_side = !split.side_by_side;
+        }
+    }
+
+    /// Move the border of the split containing the client to the given point
+    pub fn set_split_position(&mut self, client_rc: &Rc<RefCell<C>>, point: (i32, i32)) {
+        if let Some(split) = self.parent_of_mut(client_rc) {
+            let ratio = if split.side_by_side {
+                (point.0 - split.area.x()) as f32 / split.area.w().max(1) as f32
+            } else {
+                (point.1 - split.area.y()) as f32 / split.area.h().max(1) as f32
+            };
+            split.ratio = clamp_ratio(ratio);
+        }
+    }
+
+    pub fn swap(&mut self, client_a: &Rc<RefCell<C>>, client_b: &Rc<RefCell<C>>) {
+        if let Some(root) = &mut self.root {
+            root.for_each_leaf_mut(&mut |leaf| {
+                if leaf == client_a {
+                    *leaf = client_b.clone();
+                } else if leaf == client_b {
+                    *leaf = client_a.clone();
+                }
+            });
+        }
+    }
+
+    /// Make the tree contain exactly the given clients
+    ///
+    /// New clients split the first client of `focus_order` that is already part of the tree.
+    pub fn sync<'a>(&mut self, clients: &[Rc<RefCell<C>>], focus_order: impl Iterator<Item = &'a Rc<RefCell<C>>> + Clone,
+                    split_ratio: f32) where C: 'a {
+        let mut stale = Vec::new();
+        if let Some(root) = &mut self.root {
+            root.for_each_leaf_mut(&mut |leaf| if !clients.contains(leaf) { stale.push(leaf.clone()) });
+        }
+        for client_rc in stale {
+            self.remove(&client_rc);
+        }
+
+        for client_rc in clients {
+            if !self.contains(client_rc) {
+                let target = focus_order.clone().find(|c| self.contains(c)).cloned();
+                self.insert(client_rc.clone(), target.as_ref(), split_ratio);
+            }
+        }
+    }
+
+    fn parent_of_mut(&mut self, client_rc: &Rc<RefCell<C>>) -> Option<&mut BspSplit<C>> {
+        match self.root.as_mut()?.parent_node_mut(client_rc)? {
+            BspNode::Split(split) => Some(split),
+            BspNode::Leaf(_) => None,
+        }
+    }
+}
+
+impl<C: Client<Attributes>> BspNode<C> {
+    fn apply(&mut self, area: Dimensions, gap_width: u32) {
+        match self {
+            BspNode::Leaf(client_rc) => {
+                let mut client = client_rc.borrow_mut();
+                if !client.is_fullscreen() && !client.attributes().is_moving {
+                    client.move_resize(area.x(), area.y(), area.w(), area.h());
+                }
+            },
+            BspNode::Split(split) => {
+                split.area = area;
+                let (first, second) = split_area(area, split.ratio, gap_width, split.side_by_side);
+                split.first.apply(first, gap_width);
+                split.second.apply(second, gap_width);
+            },
+        }
+    }
+
+    fn contains(&self, client_rc: &Rc<RefCell<C>>) -> bool {
+        match self {
+            BspNode::Leaf(c) => c == client_rc,
+            BspNode::Split(split) => split.first.contains(client_rc) || split.second.contains(client_rc),
+        }
+    }
+
+    fn for_each_leaf_mut(&mut self, f: &mut impl FnMut(&mut Rc<RefCell<C>>)) {
+        match self {
+            BspNode::Leaf(c) => f(c),
+            BspNode::Split(split) => {
+                split.first.for_each_leaf_mut(f);
+                split.second.for_each_leaf_mut(f);
+            },
+        }
+    }
+
+    fn is_leaf(&self, client_rc: &Rc<RefCell<C>>) -> bool {
+        matches!(self, BspNode::Leaf(c) if c == client_rc)
+    }
+
+    fn last_leaf(&self) -> Rc<RefCell<C>> {
+        match self {
+            BspNode::Leaf(c) => c.clone(),
+            BspNode::Split(split) => split.second.last_leaf(),
+        }
+    }
+
+    fn leaf_mut(&mut self, client_rc: &Rc<RefCell<C>>) -> Option<&mut BspNode<C>> {
+        if self.is_leaf(client_rc) {
+            return Some(self);
+        }
+        match self {
+            BspNode::Leaf(_) => None,
+            BspNode::Split(split) => split.first.leaf_mut(client_rc).or_else(|| split.second.leaf_mut(client_rc)),
+        }
+    }
+
+    /// Find the split node that directly contains the client
+    fn parent_node_mut(&mut self, client_rc: &Rc<RefCell<C>>) -> Option<&mut BspNode<C>> {
+        let is_parent = match self {
+            BspNode::Leaf(_) => false,
+            BspNode::Split(split) => split.first.is_leaf(client_rc) || split.second.is_leaf(client_rc),
+        };
+        if is_parent {
+            return Some(self);
+        }
+        match self {
+            BspNode::Leaf(_) => None,
+            BspNode::Split(split) => split.first.parent_node_mut(client_rc)
+                .or_else(|| split.second.parent_node_mut(client_rc)),
+        }
+    }
+}
+
+fn clamp_ratio(ratio: f32) -> f32 {
+    ratio.clamp(MIN_SPLIT_RATIO, 1.0 - MIN_SPLIT_RATIO)
+}
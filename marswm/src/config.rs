@@ -1,9 +1,13 @@
 use std::cmp;
+use std::collections::BTreeMap;
 use libmars::common::*;
-use serde::{Serialize, Deserialize};
+use libmars::interfaces::wm::TitleAlignment;
+use serde::{Serialize, Deserialize, Deserializer};
 use libmars::utils::configuration::*;
+use libmars::utils::theme::*;
 
 use crate::bindings::*;
+use crate::hooks::HookEvent;
 use crate::layouts::LayoutType;
 use crate::layouts::StackMode;
 use crate::layouts::StackPosition;
@@ -28,27 +32,64 @@ pub struct Configuration {
     /// number of workspaces for secondary monitors
     pub secondary_workspaces: u32,
 
+    /// share all workspaces between monitors, so every workspace can be shown on any monitor
+    pub shared_workspaces: bool,
+
+    /// swap workspaces when switching to a workspace that is visible on another monitor (shared workspaces)
+    pub greedy_view: bool,
+
     /// script, executable or command to be executed on startup
     pub on_startup: Option<String>,
 
+    /// commands to be executed on window manager events
+    pub hooks: BTreeMap<HookEvent, String>,
+
     /// where should windows be placed initially
     pub initial_placement: WindowPlacement,
 
+    /// when the pointer should be moved to newly focused windows and monitors
+    pub warp_pointer: WarpPointer,
+
+    /// distance in pixels at which moved windows stick to screen edges and other windows (0 to disable)
+    pub edge_resistance: u32,
+
+    /// time in milliseconds to wait for the next key of a key sequence (0 to wait forever)
+    pub key_sequence_timeout: u64,
+
+    /// width in pixels of the zones at the monitor edges that snap dragged floating windows (0 to disable)
+    pub snap_zone_width: u32,
+
     /// layout configuration
+    #[serde(deserialize_with = "deserialize_layout")]
     pub layout: LayoutConfiguration,
 
+    /// settings for individual workspaces (in order of their global index)
+    pub workspaces: Vec<WorkspaceConfiguration>,
+
+    /// external programs that calculate layouts (selected with the `external` layout)
+    pub layout_generators: Vec<LayoutGeneratorConfiguration>,
+
     /// theming configuration
     pub theming: ThemingConfiguration,
 }
 
-#[derive(Serialize,Deserialize,PartialEq,Debug,Copy,Clone)]
+#[derive(Serialize,Deserialize,PartialEq,Debug,Clone)]
 #[serde(default)]
 pub struct LayoutConfiguration {
     /// default layout for each workspace
     pub default: LayoutType,
 
     /// width of the gap between windows in a tiled layout
-    pub gap_width: u32,
+    pub inner_gap: u32,
+
+    /// width of the gap between windows and the edges of the window area on each side (top, right, bottom, left)
+    pub outer_gap: (u32, u32, u32, u32),
+
+    /// remove all gaps if only one window is tiled
+    pub smart_gaps: bool,
+
+    /// remove frame and borders of tiled windows if only one of them is visible (also applies to the monocle layout)
+    pub smart_borders: bool,
 
     /// ratio of main area vs stack area in a tiled layout
     pub main_ratio: f32,
@@ -64,19 +105,89 @@ pub struct LayoutConfiguration {
 
     /// where to attach new clients in a tiling layout
     pub attach_position: AttachPosition,
+
+    /// number of columns in the grid layout (0 to keep the grid approximately square)
+    pub grid_columns: u32,
+
+    /// share that a window keeps when a new window is inserted next to it (bsp layout)
+    pub split_ratio: f32,
+}
+
+/// Layout configuration that still accepts options of older versions
+#[derive(Deserialize)]
+struct LegacyLayoutConfiguration {
+    #[serde(flatten)]
+    layout: LayoutConfiguration,
+
+    /// deprecated (sets `inner_gap` and `outer_gap`)
+    gap_width: Option<u32>,
+}
+
+#[derive(Serialize,Deserialize,PartialEq,Eq,Debug,Clone)]
+#[serde(default)]
+pub struct LayoutGeneratorConfiguration {
+    /// name to select the generator with
+    pub name: String,
+
+    /// command to start the generator
+    pub command: String,
+
+    /// time in milliseconds to wait for the generator to reply
+    pub timeout: u64,
+
+    /// built-in layout to use if the generator fails
+    pub fallback: LayoutType,
+}
+
+#[derive(Serialize,Deserialize,PartialEq,Debug,Clone)]
+#[serde(default)]
+#[derive(Default)]
+pub struct WorkspaceConfiguration {
+    /// name of the workspace (defaults to its number)
+    pub name: Option<String>,
+
+    /// glyph that is shown in front of the name
+    pub icon: Option<String>,
+
+    /// default layout of the workspace
+    pub layout: Option<LayoutType>,
+
+    /// number of windows in the main area
+    pub nmain: Option<u32>,
+
+    /// ratio of main area vs stack area
+    pub main_ratio: Option<f32>,
+
+    /// width of the gap between windows
+    pub inner_gap: Option<u32>,
+
+    /// width of the gap between windows and the edges of the window area (top, right, bottom, left)
+    pub outer_gap: Option<(u32, u32, u32, u32)>,
+
+    /// position of the stack relative to the main windows (dynamic layout)
+    pub stack_position: Option<StackPosition>,
+
+    /// layouts to cycle through (all built-in layouts if empty)
+    pub layouts: Vec<LayoutType>,
 }
 
 #[derive(Serialize,Deserialize,PartialEq,Eq,Debug,Clone)]
 #[serde(default)]
 pub struct ThemingConfiguration {
     /// color for active window frame
-    pub active_color: u64,
+    pub active_color: Color,
 
     /// color for passive window frame
-    pub inactive_color: u64,
+    pub inactive_color: Color,
 
     /// color of inner and outer border
-    pub border_color: u64,
+    pub border_color: Color,
+
+    /// frame color of windows that demand attention
+    pub urgent_color: Color,
+
+    /// frame color of unfocused pinned windows
+    pub pinned_color: Color,
 
     /// use inverted version of active/inactive color for border
     pub invert_border_color: bool,
@@ -90,6 +201,9 @@ pub struct ThemingConfiguration {
     /// width of the border around the frame
     pub outer_border_width: u32,
 
+    /// radius of the rounded frame corners (0 for square corners)
+    pub corner_radius: u32,
+
     /// decoration dimensions for clients that want no decoration
     pub no_decoration: NoDecorThemingConfiguration,
 
@@ -102,6 +216,18 @@ pub struct ThemingConfiguration {
     /// horizontal padding of title
     pub title_hpadding: u32,
 
+    /// template for the title (placeholders: `{class}`, `{instance}`, `{title}`, `{workspace}`, `{state}`)
+    pub title_format: String,
+
+    /// alignment of the title inside the frame
+    pub title_alignment: TitleAlignment,
+
+    /// buttons at the right end of the title bar (ordered from left to right)
+    pub title_buttons: Vec<TitleButton>,
+
+    /// background color of title bar buttons while hovered
+    pub title_button_hover_color: Color,
+
     /// font to use for title
     pub font: String,
 }
@@ -130,15 +256,77 @@ pub enum WindowPlacement {
     Wherever,
 }
 
+#[derive(Serialize,Deserialize,Clone,Copy,Debug,PartialEq,Eq)]
+#[serde(rename_all = "kebab-case")]
+#[derive(Default)]
+pub enum WarpPointer {
+    #[default]
+    Always,
+    OnMonitorChange,
+    Never,
+}
+
+#[derive(Serialize,Deserialize,Clone,Copy,Debug,PartialEq,Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TitleButton {
+    Close,
+    ToggleFloating,
+    ToggleFullscreen,
+    Pin,
+    Minimize,
+}
+
+impl TitleButton {
+    /// Action that is executed when the button is clicked
+    pub fn action(&self) -> BindingAction {
+        match self {
+            TitleButton::Close => BindingAction::CloseClient,
+            TitleButton::ToggleFloating => BindingAction::ToggleFloating,
+            TitleButton::ToggleFullscreen => BindingAction::ToggleFullscreen,
+            TitleButton::Pin => BindingAction::TogglePinned,
+            TitleButton::Minimize => BindingAction::MinimizeClient,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TitleButton::Close => "×",
+            TitleButton::ToggleFloating => "~",
+            TitleButton::ToggleFullscreen => "□",
+            TitleButton::Pin => "•",
+            TitleButton::Minimize => "_",
+        }
+    }
+}
+
+impl WarpPointer {
+    pub fn warps(&self, changes_monitor: bool) -> bool {
+        match self {
+            WarpPointer::Always => true,
+            WarpPointer::OnMonitorChange => changes_monitor,
+            WarpPointer::Never => false,
+        }
+    }
+}
+
 
 impl Default for Configuration {
     fn default() -> Self {
         Configuration {
             primary_workspaces: 8,
             secondary_workspaces: 2,
+            shared_workspaces: false,
+            greedy_view: true,
             on_startup: None,
+            hooks: BTreeMap::new(),
             initial_placement: WindowPlacement::default(),
+            warp_pointer: WarpPointer::default(),
+            edge_resistance: 10,
+            key_sequence_timeout: 2000,
+            snap_zone_width: 10,
             layout: LayoutConfiguration::default(),
+            workspaces: Vec::new(),
+            layout_generators: Vec::new(),
             theming: ThemingConfiguration::default(),
         }
     }
@@ -148,12 +336,28 @@ impl Default for LayoutConfiguration {
     fn default() -> Self {
         LayoutConfiguration {
             default: LayoutType::Floating,
-            gap_width: 0,
+            inner_gap: 0,
+            outer_gap: (0, 0, 0, 0),
+            smart_gaps: false,
+            smart_borders: false,
             main_ratio: 0.6,
             nmain: 1,
             stack_position: StackPosition::Right,
             stack_mode: StackMode::Split,
             attach_position: AttachPosition::Main,
+            grid_columns: 0,
+            split_ratio: 0.5,
+        }
+    }
+}
+
+impl Default for LayoutGeneratorConfiguration {
+    fn default() -> Self {
+        LayoutGeneratorConfiguration {
+            name: String::new(),
+            command: String::new(),
+            timeout: 200,
+            fallback: LayoutType::Stack,
         }
     }
 }
@@ -161,17 +365,24 @@ impl Default for LayoutConfiguration {
 impl Default for ThemingConfiguration {
     fn default() -> Self {
         ThemingConfiguration {
-            active_color: 0x5f875f,
-            inactive_color: 0x262626,
-            border_color: 0x262626,
+            active_color: Color::named("color2"),
+            inactive_color: Color::named("background"),
+            border_color: Color::named("background"),
+            urgent_color: Color::named("color1"),
+            pinned_color: Color::named("background"),
             invert_border_color: false,
             frame_width: (15, 2, 2, 2),
             inner_border_width: 0,
             outer_border_width: 0,
+            corner_radius: 0,
             no_decoration: NoDecorThemingConfiguration::default(),
             show_title: false,
             title_vpadding: 0,
             title_hpadding: 5,
+            title_format: "{title}".to_owned(),
+            title_alignment: TitleAlignment::default(),
+            title_buttons: Vec::new(),
+            title_button_hover_color: Color::named("color10"),
             font: "serif".to_owned(),
         }
     }
@@ -181,6 +392,42 @@ impl Default for ThemingConfiguration {
 
 
 
+impl ThemingConfiguration {
+    /// Look up named colors in the palette
+    pub fn apply_palette(&mut self, palette: &Palette) {
+        for color in [&mut self.active_color, &mut self.inactive_color, &mut self.border_color,
+                      &mut self.urgent_color, &mut self.pinned_color, &mut self.title_button_hover_color] {
+            color.resolve(palette);
+        }
+    }
+}
+
+impl WorkspaceConfiguration {
+    /// Layout configuration of the workspace based on the global one
+    pub fn layout_config(&self, base: &LayoutConfiguration) -> LayoutConfiguration {
+        let mut config = base.clone();
+        if let Some(layout) = &self.layout {
+            config.default = layout.clone();
+        }
+        if let Some(nmain) = self.nmain {
+            config.nmain = nmain;
+        }
+        if let Some(main_ratio) = self.main_ratio {
+            config.main_ratio = main_ratio;
+        }
+        if let Some(inner_gap) = self.inner_gap {
+            config.inner_gap = inner_gap;
+        }
+        if let Some(outer_gap) = self.outer_gap {
+            config.outer_gap = outer_gap;
+        }
+        if let Some(stack_position) = self.stack_position {
+            config.stack_position = stack_position;
+        }
+        config
+    }
+}
+
 impl WindowPlacement {
     pub fn calc(&self, client_dimensions: Dimensions, window_area: Dimensions, pointer: (i32, i32)) -> (i32, i32) {
         use WindowPlacement::*;
@@ -206,6 +453,16 @@ impl WindowPlacement {
 }
 
 
+fn deserialize_layout<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LayoutConfiguration, D::Error> {
+    let LegacyLayoutConfiguration { mut layout, gap_width } = LegacyLayoutConfiguration::deserialize(deserializer)?;
+    if let Some(gap_width) = gap_width {
+        eprintln!("The option 'gap_width' is deprecated, use 'inner_gap' and 'outer_gap' instead");
+        layout.inner_gap = gap_width;
+        layout.outer_gap = (gap_width, gap_width, gap_width, gap_width);
+    }
+    Ok(layout)
+}
+
 pub fn read_button_bindings() -> Vec<ButtonBinding> {
     // read keybindings file
     let mut button_bindings = match read_config_file(CONFIG_DIR, BUTTON_BINDINGS_FILE) {
@@ -228,6 +485,9 @@ pub fn read_button_bindings() -> Vec<ButtonBinding> {
 }
 
 pub fn read_config() -> Configuration {
+    // named colors are looked up in the palette of the theme
+    read_palette();
+
     match read_config_file(CONFIG_DIR, CONFIG_FILE) {
         Ok(config) => config,
         Err(msg) => {
@@ -0,0 +1,198 @@
+use libmars::common::*;
+use std::cell::RefCell;
+use std::cmp;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::LayoutGeneratorConfiguration;
+use crate::layouts::LayoutType;
+
+
+/// Delay before a failed generator is restarted (doubled for every consecutive failure)
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+thread_local! {
+    /// Layout generators known to the window manager (processes are started on first use)
+    static GENERATORS: RefCell<Vec<LayoutGenerator>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Parameters that are sent to a layout generator
+pub struct LayoutRequest<'a> {
+    pub win_area: Dimensions,
+    pub nmain: u32,
+    pub main_ratio: f32,
+    pub gap_width: u32,
+    /// application names of the tiled clients
+    pub classes: &'a [String],
+}
+
+/// External process that calculates the geometry of tiled windows
+///
+/// For every layout request the window manager writes a line with the window area (without outer gaps),
+/// the number of clients, `nmain`, `main_ratio` and the inner gap width (separated by spaces) to the standard input of the generator,
+/// followed by one line with the application name for each client.
+/// The generator has to reply with one line `x y width height` per client on its standard output.
+struct LayoutGenerator {
+    config: LayoutGeneratorConfiguration,
+    process: Option<GeneratorProcess>,
+    unknown: bool,
+    failures: u32,  // consecutive failures
+    restart_at: Option<Instant>,  // the fallback is used until then
+}
+
+struct GeneratorProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+/// Make the configured layout generators available for the `external` layout
+pub fn register_layout_generators(configs: &[LayoutGeneratorConfiguration]) {
+    GENERATORS.with_borrow_mut(|generators| {
+        generators.clear();
+        for config in configs {
+            let mut config = config.clone();
+            // the bsp layout is not calculated from the list of clients, so it can't act as fallback either
+            if matches!(config.fallback, LayoutType::External(_) | LayoutType::Bsp) {
+                eprintln!("Fallback for layout generator '{}' must be a built-in layout other than bsp", config.name);
+                config.fallback = LayoutGeneratorConfiguration::default().fallback;
+            }
+            generators.push(LayoutGenerator::new(config, false));
+        }
+    });
+}
+
+/// Request the geometry of the clients from the generator `name`
+///
+/// Returns the fallback layout of the generator if it is unknown or did not respond properly.
+pub fn generate_layout(name: &str, request: &LayoutRequest) -> Result<Vec<Dimensions>, LayoutType> {
+    GENERATORS.with_borrow_mut(|generators| {
+        let generator = match generators.iter_mut().find(|g| g.config.name == name) {
+            Some(generator) => generator,
+            None => {
+                // remember the unknown generator to report it only once
+                eprintln!("Unknown layout generator '{}'", name);
+                let config = LayoutGeneratorConfiguration { name: name.to_owned(), ..Default::default() };
+                let fallback = config.fallback.clone();
+                generators.push(LayoutGenerator::new(config, true));
+                return Err(fallback);
+            },
+        };
+
+        if generator.unknown || generator.restart_at.is_some_and(|t| Instant::now() < t) {
+            return Err(generator.config.fallback.clone());
+        }
+
+        match generator.generate(request) {
+            Ok(dimensions) => {
+                generator.failures = 0;
+                generator.restart_at = None;
+                Ok(dimensions)
+            },
+            Err(msg) => {
+                let delay = generator.fail();
+                eprintln!("Layout generator '{}' failed: {} (falling back to {:?} for {}s)",
+                          name, msg, generator.config.fallback, delay.as_secs());
+                Err(generator.config.fallback.clone())
+            },
+        }
+    })
+}
+
+impl LayoutGenerator {
+    fn new(config: LayoutGeneratorConfiguration, unknown: bool) -> Self {
+        LayoutGenerator { config, process: None, unknown, failures: 0, restart_at: None }
+    }
+
+    /// Stop the process after a failure and schedule its restart
+    fn fail(&mut self) -> Duration {
+        self.stop();
+        let delay = cmp::min(RESTART_DELAY.saturating_mul(1 << cmp::min(self.failures, 16)), MAX_RESTART_DELAY);
+        self.failures += 1;
+        self.restart_at = Some(Instant::now() + delay);
+        delay
+    }
+
+    fn generate(&mut self, request: &LayoutRequest) -> Result<Vec<Dimensions>, String> {
+        if self.process.is_none() {
+            self.process = Some(GeneratorProcess::spawn(&self.config.command)?);
+        }
+        let process = self.process.as_mut().unwrap();
+
+        // discard surplus lines of previous replies, so they are not mistaken for the reply to this request
+        while process.lines.try_recv().is_ok() {}
+
+        let area = request.win_area;
+        let mut message = format!("{} {} {} {} {} {} {} {}\n", area.x(), area.y(), area.w(), area.h(),
+                                  request.classes.len(), request.nmain, request.main_ratio, request.gap_width);
+        for class in request.classes {
+            message.push_str(&class.replace('\n', " "));
+            message.push('\n');
+        }
+        process.stdin.write_all(message.as_bytes())
+            .and_then(|_| process.stdin.flush())
+            .map_err(|e| format!("unable to send request ({})", e))?;
+
+        let deadline = Instant::now() + Duration::from_millis(self.config.timeout);
+        let mut dimensions = Vec::with_capacity(request.classes.len());
+        while dimensions.len() < request.classes.len() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = process.lines.recv_timeout(remaining)
+                .map_err(|_| "no reply in time".to_owned())?;
+            dimensions.push(parse_dimensions(&line)?);
+        }
+        Ok(dimensions)
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            let _ignored = process.child.kill();
+            let _ignored = process.child.wait();
+        }
+    }
+}
+
+impl GeneratorProcess {
+    fn spawn(command: &str) -> Result<GeneratorProcess, String> {
+        let mut child = Command::new("sh").arg("-c").arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("unable to start '{}' ({})", command, e))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // replies are read on a separate thread, so waiting for them can time out
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(GeneratorProcess { child, stdin, lines })
+    }
+}
+
+fn parse_dimensions(line: &str) -> Result<Dimensions, String> {
+    let invalid = || format!("invalid reply '{}'", line);
+    let values: Vec<&str> = line.split_whitespace().collect();
+    if values.len() != 4 {
+        return Err(invalid());
+    }
+
+    let x = values[0].parse().map_err(|_| invalid())?;
+    let y = values[1].parse().map_err(|_| invalid())?;
+    let w = values[2].parse().map_err(|_| invalid())?;
+    let h = values[3].parse().map_err(|_| invalid())?;
+    if w == 0 || h == 0 {
+        return Err(invalid());
+    }
+    Ok(Dimensions::new(x, y, w, h))
+}
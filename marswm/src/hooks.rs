@@ -0,0 +1,97 @@
+use libmars::interfaces::wm::Client;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+use crate::attributes::*;
+use crate::layouts::LayoutType;
+use crate::workspace::Workspace;
+
+
+/// Events that can trigger a hook command
+#[derive(Serialize,Deserialize,Clone,Copy,Debug,PartialEq,Eq,PartialOrd,Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    ClientManage,
+    ClientUnmanage,
+    FocusChange,
+    WorkspaceSwitch,
+    LayoutChange,
+    MonitorAdded,
+    MonitorRemoved,
+    FullscreenEnter,
+    FullscreenExit,
+}
+
+impl HookEvent {
+    /// Name of the event as used in the configuration
+    pub fn name(&self) -> &'static str {
+        use HookEvent::*;
+        match self {
+            ClientManage => "client-manage",
+            ClientUnmanage => "client-unmanage",
+            FocusChange => "focus-change",
+            WorkspaceSwitch => "workspace-switch",
+            LayoutChange => "layout-change",
+            MonitorAdded => "monitor-added",
+            MonitorRemoved => "monitor-removed",
+            FullscreenEnter => "fullscreen-enter",
+            FullscreenExit => "fullscreen-exit",
+        }
+    }
+}
+
+/// Information about an event that is passed to hook commands as environment variables
+#[derive(Default)]
+pub struct HookContext {
+    vars: Vec<(&'static str, String)>,
+}
+
+impl HookContext {
+    pub fn new() -> HookContext {
+        HookContext::default()
+    }
+
+    pub fn client<C: Client<Attributes>>(mut self, client: &C) -> HookContext {
+        self.vars.push(("WINDOW_ID", client.id().to_string()));
+        self.vars.push(("WINDOW_CLASS", client.application()));
+        self.vars.push(("WINDOW_TITLE", client.title()));
+        self
+    }
+
+    pub fn layout(mut self, layout: &LayoutType) -> HookContext {
+        self.vars.push(("LAYOUT", layout.to_string()));
+        self
+    }
+
+    pub fn monitor(mut self, name: &str) -> HookContext {
+        self.vars.push(("MONITOR", name.to_owned()));
+        self
+    }
+
+    pub fn workspace<C: Client<Attributes>>(mut self, workspace: &Workspace<C>) -> HookContext {
+        self.vars.push(("WORKSPACE", workspace.global_index().to_string()));
+        self.vars.push(("WORKSPACE_NAME", workspace.name().to_owned()));
+        self
+    }
+}
+
+/// Run the command configured for `event` (if there is one)
+pub fn run_hook(hooks: &BTreeMap<HookEvent, String>, event: HookEvent, context: HookContext) {
+    let command = match hooks.get(&event) {
+        Some(command) => command,
+        None => return,
+    };
+
+    let result = std::process::Command::new("sh").arg("-c").arg(command)
+        .env("EVENT", event.name())
+        .envs(context.vars)
+        .spawn();
+    match result {
+        Ok(mut handle) => {
+            std::thread::spawn(move || {
+                let _ignored = handle.wait();
+            });
+        },
+        Err(e) => eprintln!("Unable to run hook for {:?}: {}", event, e),
+    }
+}
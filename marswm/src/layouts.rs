@@ -3,24 +3,31 @@ use libmars::enum_with_values;
 use libmars::interfaces::wm::Client;
 use serde::{Serialize, Deserialize};
 use std::cell::RefCell;
+use std::fmt;
 use std::rc::Rc;
 
 use crate::attributes::*;
 use crate::config::LayoutConfiguration;
-
-
-enum_with_values! {
-    #[derive(Serialize,Deserialize,Clone,Copy,Debug,PartialEq,Eq)]
-    #[serde(rename_all = "kebab-case")]
-    vis pub enum LayoutType {
-        Floating,
-        Stack,
-        BottomStack,
-        Monocle,
-        Deck,
-        Dynamic,
-        Centered
-    }
+use crate::generator::*;
+
+
+#[derive(Serialize,Deserialize,Clone,Debug,PartialEq,Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutType {
+    Floating,
+    Stack,
+    BottomStack,
+    Monocle,
+    Deck,
+    Dynamic,
+    Centered,
+    Grid,
+    Columns,
+    Spiral,
+    Dwindle,
+    Bsp,
+    /// layout calculated by the external layout generator with the given name
+    External(String),
 }
 
 enum_with_values! {
@@ -53,39 +60,76 @@ enum_with_values! {
     }
 }
 
-pub struct Layout<C: Client<Attributes>> {
-    apply: fn(Dimensions, &[Rc<RefCell<C>>], &LayoutConfiguration),
+pub enum Layout<C: Client<Attributes>> {
+    BuiltIn(fn(Dimensions, &[Rc<RefCell<C>>], &LayoutConfiguration)),
+    /// geometry is requested from a layout generator (see [crate::generator])
+    External(String),
+}
+
+impl LayoutType {
+    /// Built-in layouts in the order they are cycled through
+    pub const VALUES: &'static [LayoutType] = &[
+        LayoutType::Floating,
+        LayoutType::Stack,
+        LayoutType::BottomStack,
+        LayoutType::Monocle,
+        LayoutType::Deck,
+        LayoutType::Dynamic,
+        LayoutType::Centered,
+        LayoutType::Grid,
+        LayoutType::Columns,
+        LayoutType::Spiral,
+        LayoutType::Dwindle,
+        LayoutType::Bsp,
+    ];
+}
+
+impl fmt::Display for LayoutType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            LayoutType::Floating => "floating",
+            LayoutType::Stack => "stack",
+            LayoutType::BottomStack => "bottom-stack",
+            LayoutType::Monocle => "monocle",
+            LayoutType::Deck => "deck",
+            LayoutType::Dynamic => "dynamic",
+            LayoutType::Centered => "centered",
+            LayoutType::Grid => "grid",
+            LayoutType::Columns => "columns",
+            LayoutType::Spiral => "spiral",
+            LayoutType::Dwindle => "dwindle",
+            LayoutType::Bsp => "bsp",
+            LayoutType::External(name) => return write!(f, "external {}", name),
+        };
+        write!(f, "{}", name)
+    }
 }
 
 impl<C: Client<Attributes>> Layout<C> {
-    pub fn get(layout_type: LayoutType) -> Layout<C> {
+    pub fn get(layout_type: &LayoutType) -> Layout<C> {
         match layout_type {
-            LayoutType::Floating => Layout {
-                apply: |_, _, _| {},
-            },
-            LayoutType::Stack => Layout {
-                apply: apply_layout_stack,
-            },
-            LayoutType::BottomStack => Layout {
-                apply: apply_layout_bottom_stack,
-            },
-            LayoutType::Monocle => Layout {
-                apply: apply_layout_monocle,
-            },
-            LayoutType::Deck => Layout {
-                apply: apply_layout_deck,
-            },
-            LayoutType::Dynamic => Layout {
-                apply: apply_layout_dynamic,
-            },
-            LayoutType::Centered => Layout {
-                apply: apply_layout_centered,
-            },
+            LayoutType::Floating => Layout::BuiltIn(|_, _, _| {}),
+            LayoutType::Stack => Layout::BuiltIn(apply_layout_stack),
+            LayoutType::BottomStack => Layout::BuiltIn(apply_layout_bottom_stack),
+            LayoutType::Monocle => Layout::BuiltIn(apply_layout_monocle),
+            LayoutType::Deck => Layout::BuiltIn(apply_layout_deck),
+            LayoutType::Dynamic => Layout::BuiltIn(apply_layout_dynamic),
+            LayoutType::Centered => Layout::BuiltIn(apply_layout_centered),
+            LayoutType::Grid => Layout::BuiltIn(apply_layout_grid),
+            LayoutType::Columns => Layout::BuiltIn(apply_layout_columns),
+            LayoutType::Spiral => Layout::BuiltIn(apply_layout_spiral),
+            LayoutType::Dwindle => Layout::BuiltIn(apply_layout_dwindle),
+            // the split tree is kept by the workspace
+            LayoutType::Bsp => Layout::BuiltIn(|_, _, _| {}),
+            LayoutType::External(name) => Layout::External(name.clone()),
         }
     }
 
     pub fn apply_layout(&self, win_area: Dimensions, clients: &[Rc<RefCell<C>>], config: &LayoutConfiguration) {
-        (self.apply)(win_area, clients, config);
+        match self {
+            Layout::BuiltIn(apply) => apply(win_area, clients, config),
+            Layout::External(name) => apply_layout_external(name, win_area, clients, config),
+        }
     }
 }
 
@@ -95,14 +139,17 @@ fn apply_layout_centered<C: Client<Attributes>>(win_area: Dimensions, clients: &
     let mut stack_clients_0: Vec<_> = clients.collect();
     let stack_clients_1 = stack_clients_0.split_off(stack_clients_0.len() / 2);
     let adjusted_main_ratio = config.main_ratio / (2. - config.main_ratio);
+    let area = inner_area(win_area, config.outer_gap);
+    let gap_share = (config.inner_gap / 2, config.inner_gap - (config.inner_gap / 2));
 
     let (main_width, main_dimensions) = if !main_clients.is_empty() {
-        let main_width: u32 = (win_area.w() as f32 * adjusted_main_ratio) as u32;
+        let main_width: u32 = (area.w() as f32 * adjusted_main_ratio) as u32;
+        let stack_width = (area.w() - main_width) / 2;
         let main_dimensions = Dimensions::new(
-            win_area.center().0 - main_width as i32 / 2 + config.gap_width as i32 / 2,
-            win_area.y() + config.gap_width as i32,
-            main_width.saturating_sub(config.gap_width),
-            win_area.h().saturating_sub(config.gap_width * 2),
+            area.x() + stack_width as i32 + gap_share.0 as i32,
+            area.y(),
+            main_width.saturating_sub(config.inner_gap),
+            area.h(),
         );
         (main_width, main_dimensions)
     } else {
@@ -112,31 +159,63 @@ fn apply_layout_centered<C: Client<Attributes>>(win_area: Dimensions, clients: &
     };
 
 
-    let stack_width_0 = (win_area.w() - main_width) / 2;
-    let stack_width_1 = win_area.w() - main_width - stack_width_0;
+    let stack_width_0 = (area.w() - main_width) / 2;
+    let stack_width_1 = area.w() - main_width - stack_width_0;
 
     let stack_dimensions_0 = Dimensions::new(
-        win_area.x() + config.gap_width as i32,
-        win_area.y() + config.gap_width as i32,
-        stack_width_0.saturating_sub(config.gap_width + config.gap_width / 2),
-        win_area.h().saturating_sub(config.gap_width * 2),
+        area.x(),
+        area.y(),
+        stack_width_0.saturating_sub(gap_share.1),
+        area.h(),
     );
 
     let stack_dimensions_1 = Dimensions::new(
-        win_area.x() + stack_width_0 as i32 + main_width as i32 + config.gap_width as i32 / 2,
-        win_area.y() + config.gap_width as i32,
-        stack_width_1.saturating_sub(config.gap_width + config.gap_width / 2),
-        win_area.h().saturating_sub(config.gap_width * 2),
+        area.x() + stack_width_0 as i32 + main_width as i32 + gap_share.0 as i32,
+        area.y(),
+        stack_width_1.saturating_sub(gap_share.0),
+        area.h(),
     );
 
-    stack_clients_horizontally(main_dimensions, main_clients, config.gap_width);
-    stack_clients_vertically(stack_dimensions_0, stack_clients_0, config.gap_width);
-    stack_clients_vertically(stack_dimensions_1, stack_clients_1, config.gap_width);
+    stack_clients_horizontally(main_dimensions, main_clients, config.inner_gap);
+    stack_clients_vertically(stack_dimensions_0, stack_clients_0, config.inner_gap);
+    stack_clients_vertically(stack_dimensions_1, stack_clients_1, config.inner_gap);
 }
 
 
+fn apply_layout_columns(win_area: Dimensions, clients: &[Rc<RefCell<impl Client<Attributes>>>], config: &LayoutConfiguration) {
+    let clients = clients.iter().collect();
+    stack_clients_horizontally(inner_area(win_area, config.outer_gap), clients, config.inner_gap);
+}
+
+fn apply_layout_grid(win_area: Dimensions, clients: &[Rc<RefCell<impl Client<Attributes>>>], config: &LayoutConfiguration) {
+    let nclients: u32 = clients.len().try_into().unwrap();
+    if nclients == 0 {
+        return;
+    }
+
+    // use as many columns as configured or make the grid approximately square
+    let ncolumns = if config.grid_columns > 0 {
+        u32::min(config.grid_columns, nclients)
+    } else {
+        (1..=nclients).find(|c| c * c >= nclients).unwrap()
+    };
+    let nrows = nclients.div_ceil(ncolumns);
+
+    // the rows are laid out like vertically stacked clients, the last row may contain fewer clients
+    let area = inner_area(win_area, config.outer_gap);
+    let total_gaps = (nrows - 1) * config.inner_gap;
+    let height = (area.h().saturating_sub(total_gaps)) / nrows;
+    let height_remainder = area.h().saturating_sub(nrows * height + total_gaps);
+    for (i, row) in clients.chunks(ncolumns as usize).enumerate() {
+        let y = area.y() + (i as i32 * (height + config.inner_gap) as i32);
+        let height_adjustment = if i as u32 == nrows - 1 { height_remainder } else { 0 };
+        let row_area = Dimensions::new(area.x(), y, area.w(), height + height_adjustment);
+        stack_clients_horizontally(row_area, row.iter().collect(), config.inner_gap);
+    }
+}
+
 fn apply_layout_bottom_stack<C: Client<Attributes>>(win_area: Dimensions, clients: &[Rc<RefCell<C>>], config: &LayoutConfiguration) {
-    let mut config = *config;
+    let mut config = config.clone();
     config.stack_position = StackPosition::Bottom;
     config.stack_mode = StackMode::Split;
     apply_layout_dynamic(win_area, clients, &config);
@@ -147,15 +226,16 @@ fn apply_layout_dynamic<C: Client<Attributes>>(win_area: Dimensions, clients: &[
     let mut clients = clients.iter();
     let main_clients = (&mut clients).take(config.nmain.try_into().unwrap()).collect();
     let stack_clients = clients.collect();
+    let win_area = inner_area(win_area, config.outer_gap);
 
     let (areas, swap) = match config.stack_position {
-        StackPosition::Left => (layout_dimensions_horizontal(win_area, 1.0 - config.main_ratio, config.gap_width,
+        StackPosition::Left => (layout_dimensions_horizontal(win_area, 1.0 - config.main_ratio, config.inner_gap,
                                                              config.nmain, nclients), true),
-        StackPosition::Top => (layout_dimensions_vertical(win_area, 1.0 - config.main_ratio, config.gap_width,
+        StackPosition::Top => (layout_dimensions_vertical(win_area, 1.0 - config.main_ratio, config.inner_gap,
                                                           config.nmain, nclients), true),
-        StackPosition::Right => (layout_dimensions_horizontal(win_area, config.main_ratio, config.gap_width,
+        StackPosition::Right => (layout_dimensions_horizontal(win_area, config.main_ratio, config.inner_gap,
                                                               config.nmain, nclients), false),
-        StackPosition::Bottom => (layout_dimensions_vertical(win_area, config.main_ratio, config.gap_width,
+        StackPosition::Bottom => (layout_dimensions_vertical(win_area, config.main_ratio, config.inner_gap,
                                                              config.nmain, nclients), false),
     };
 
@@ -168,10 +248,10 @@ fn apply_layout_dynamic<C: Client<Attributes>>(win_area: Dimensions, clients: &[
 
     match config.stack_position {
         StackPosition::Left | StackPosition::Right => {
-            stack_clients_vertically(main_area, main_clients, config.gap_width);
+            stack_clients_vertically(main_area, main_clients, config.inner_gap);
         },
         StackPosition::Top | StackPosition::Bottom => {
-            stack_clients_horizontally(main_area, main_clients, config.gap_width);
+            stack_clients_horizontally(main_area, main_clients, config.inner_gap);
         }
     }
 
@@ -179,17 +259,76 @@ fn apply_layout_dynamic<C: Client<Attributes>>(win_area: Dimensions, clients: &[
         StackMode::Deck => stack_clients_ontop(stack_area, stack_clients),
         StackMode::Split => match config.stack_position {
             StackPosition::Left | StackPosition::Right => {
-                stack_clients_vertically(stack_area, stack_clients, config.gap_width);
+                stack_clients_vertically(stack_area, stack_clients, config.inner_gap);
             },
             StackPosition::Top | StackPosition::Bottom => {
-                stack_clients_horizontally(stack_area, stack_clients, config.gap_width);
+                stack_clients_horizontally(stack_area, stack_clients, config.inner_gap);
             }
         }
     }
 }
 
+fn apply_layout_dwindle(win_area: Dimensions, clients: &[Rc<RefCell<impl Client<Attributes>>>], config: &LayoutConfiguration) {
+    apply_layout_fibonacci(win_area, clients, config, false);
+}
+
+fn apply_layout_spiral(win_area: Dimensions, clients: &[Rc<RefCell<impl Client<Attributes>>>], config: &LayoutConfiguration) {
+    apply_layout_fibonacci(win_area, clients, config, true);
+}
+
+fn apply_layout_external<C: Client<Attributes>>(name: &str, win_area: Dimensions, clients: &[Rc<RefCell<C>>],
+                                                config: &LayoutConfiguration) {
+    let classes: Vec<String> = clients.iter().map(|c| c.borrow().application()).collect();
+    let request = LayoutRequest {
+        win_area: inner_area(win_area, config.outer_gap),
+        nmain: config.nmain,
+        main_ratio: config.main_ratio,
+        gap_width: config.inner_gap,
+        classes: &classes,
+    };
+
+    match generate_layout(name, &request) {
+        Ok(dimensions) => {
+            for (client_rc, dimensions) in clients.iter().zip(dimensions) {
+                let mut client = client_rc.borrow_mut();
+                if !client.is_fullscreen() && !client.attributes().is_moving {
+                    client.move_resize(dimensions.x(), dimensions.y(), dimensions.w(), dimensions.h());
+                }
+            }
+        },
+        Err(fallback) => Layout::get(&fallback).apply_layout(win_area, clients, config),
+    }
+}
+
+/// Split the remaining area in half for every client, alternating between vertical and horizontal splits
+///
+/// With `spiral` the clients are arranged clockwise towards the center,
+/// otherwise they dwindle towards the bottom right corner.
+fn apply_layout_fibonacci(win_area: Dimensions, clients: &[Rc<RefCell<impl Client<Attributes>>>],
+                          config: &LayoutConfiguration, spiral: bool) {
+    let mut area = inner_area(win_area, config.outer_gap);
+    for (i, client_rc) in clients.iter().enumerate() {
+        let client_area = if i == clients.len() - 1 {
+            area
+        } else {
+            let ratio = if i == 0 { config.main_ratio } else { 0.5 };
+            let (first, second) = split_area(area, ratio, config.inner_gap, i % 2 == 0);
+
+            // a spiral places every other client of each axis at the far end of the remaining area
+            if spiral && i % 4 >= 2 {
+                area = first;
+                second
+            } else {
+                area = second;
+                first
+            }
+        };
+        stack_clients_ontop(client_area, vec![client_rc]);
+    }
+}
+
 fn apply_layout_stack<C: Client<Attributes>>(win_area: Dimensions, clients: &[Rc<RefCell<C>>], config: &LayoutConfiguration) {
-    let mut config = *config;
+    let mut config = config.clone();
     config.stack_position = StackPosition::Right;
     config.stack_mode = StackMode::Split;
     apply_layout_dynamic(win_area, clients, &config);
@@ -201,81 +340,58 @@ fn apply_layout_monocle(win_area: Dimensions, clients: &[Rc<RefCell<impl Client<
 }
 
 fn apply_layout_deck(win_area: Dimensions, clients: &[Rc<RefCell<impl Client<Attributes>>>], config: &LayoutConfiguration) {
-    let mut config = *config;
+    let mut config = config.clone();
     config.stack_position = StackPosition::Right;
     config.stack_mode = StackMode::Deck;
     apply_layout_dynamic(win_area, clients, &config);
 }
 
-fn layout_dimensions_horizontal(win_area: Dimensions, ratio: f32, gap_width: u32, nmain: u32, nclients: u32) -> (Dimensions, Dimensions) {
-    let first_width: u32 = (win_area.w() as f32 * ratio) as u32;
-    let gap_share = (gap_width / 2, gap_width - (gap_width / 2));
+/// Window area without the outer gaps
+///
+/// * `gaps` - Gaps on each side (top, right, bottom, left)
+pub fn inner_area(win_area: Dimensions, gaps: (u32, u32, u32, u32)) -> Dimensions {
+    let (top, right, bottom, left) = gaps;
+    Dimensions::new(win_area.x() + left as i32, win_area.y() + top as i32,
+                    win_area.w().saturating_sub(left + right), win_area.h().saturating_sub(top + bottom))
+}
 
+/// Split an area into two parts separated by a gap
+///
+/// * `ratio` - Share of the first part
+/// * `side_by_side` - Whether to place the parts next to each other instead of below each other
+pub fn split_area(area: Dimensions, ratio: f32, gap_width: u32, side_by_side: bool) -> (Dimensions, Dimensions) {
+    if side_by_side {
+        let available = area.w().saturating_sub(gap_width);
+        let first_w = (available as f32 * ratio) as u32;
+        let first = Dimensions::new(area.x(), area.y(), first_w, area.h());
+        let second = Dimensions::new(area.x() + (first_w + gap_width) as i32, area.y(), available - first_w, area.h());
+        (first, second)
+    } else {
+        let available = area.h().saturating_sub(gap_width);
+        let first_h = (available as f32 * ratio) as u32;
+        let first = Dimensions::new(area.x(), area.y(), area.w(), first_h);
+        let second = Dimensions::new(area.x(), area.y() + (first_h + gap_width) as i32, area.w(), available - first_h);
+        (first, second)
+    }
+}
+
+fn layout_dimensions_horizontal(area: Dimensions, ratio: f32, gap_width: u32, nmain: u32, nclients: u32) -> (Dimensions, Dimensions) {
     if nmain == 0 {  // all windows in stack area
-        let first_area = Dimensions::new(0, 0, 0, 0);
-        let second_x = win_area.x() + gap_width as i32;
-        let second_y = win_area.y() + gap_width as i32;
-        let second_w = win_area.w().saturating_sub(2 * gap_width);
-        let second_h = win_area.h().saturating_sub(2 * gap_width);
-        let second_area = Dimensions::new(second_x, second_y, second_w, second_h);
-        (first_area, second_area)
+        (Dimensions::new(0, 0, 0, 0), area)
     } else if nclients <= nmain {  // no windows in stack area
-        let second_area = Dimensions::new(0, 0, 0, 0);
-        let first_x = win_area.x() + gap_width as i32;
-        let first_y = win_area.y() + gap_width as i32;
-        let first_w = win_area.w().saturating_sub(2 * gap_width);
-        let first_h = win_area.h().saturating_sub(2 * gap_width);
-        let first_area = Dimensions::new(first_x, first_y, first_w, first_h);
-        (first_area, second_area)
+        (area, Dimensions::new(0, 0, 0, 0))
     } else {
-        let first_x = win_area.x() + gap_width as i32;
-        let first_y = win_area.y() + gap_width as i32;
-        let first_w = first_width.saturating_sub(gap_width).saturating_sub(gap_share.0);
-        let first_h = win_area.h().saturating_sub(2 * gap_width);
-        let first_area = Dimensions::new(first_x, first_y, first_w, first_h);
-
-        let second_x = win_area.x() + first_width as i32 + gap_share.0 as i32;
-        let second_y = win_area.y() + gap_width as i32;
-        let second_w = win_area.w().saturating_sub(first_width).saturating_sub(gap_width).saturating_sub(gap_share.1);
-        let second_h = win_area.h().saturating_sub(2 * gap_width);
-        let second_area = Dimensions::new(second_x, second_y, second_w, second_h);
-        (first_area, second_area)
+        split_area(area, ratio, gap_width, true)
     }
 }
 
-fn layout_dimensions_vertical(win_area: Dimensions, ratio: f32, gap_width: u32, nmain: u32, nclients: u32) -> (Dimensions, Dimensions) {
-    let first_height: u32 = (win_area.h() as f32 * ratio) as u32;
-    let gap_share = (gap_width / 2, gap_width - (gap_width / 2));
-
+fn layout_dimensions_vertical(area: Dimensions, ratio: f32, gap_width: u32, nmain: u32, nclients: u32) -> (Dimensions, Dimensions) {
     if nmain == 0 {  // all windows in stack area
-        let first_area = Dimensions::new(0, 0, 0, 0);
-        let second_x = win_area.x() + gap_width as i32;
-        let second_y = win_area.y() + gap_width as i32;
-        let second_w = win_area.w().saturating_sub(2 * gap_width);
-        let second_h = win_area.h().saturating_sub(2 * gap_width);
-        let second_area = Dimensions::new(second_x, second_y, second_w, second_h);
-        (first_area, second_area)
+        (Dimensions::new(0, 0, 0, 0), area)
     } else if nclients <= nmain {  // no windows in stack area
-        let second_area = Dimensions::new(0, 0, 0, 0);
-        let first_x = win_area.x() + gap_width as i32;
-        let first_y = win_area.y() + gap_width as i32;
-        let first_w = win_area.w().saturating_sub(2 * gap_width);
-        let first_h = win_area.h().saturating_sub(2 * gap_width);
-        let first_area = Dimensions::new(first_x, first_y, first_w, first_h);
-        (first_area, second_area)
+        (area, Dimensions::new(0, 0, 0, 0))
     } else {
-        let first_x = win_area.x() + gap_width as i32;
-        let first_y = win_area.y() + gap_width as i32;
-        let first_w = win_area.w().saturating_sub(2 * gap_width);
-        let first_h = first_height.saturating_sub(gap_width).saturating_sub(gap_share.0);
-        let first_area = Dimensions::new(first_x, first_y, first_w, first_h);
-
-        let second_x = win_area.x() + gap_width as i32;
-        let second_y = win_area.y() + first_height as i32 + gap_share.0 as i32;
-        let second_w = win_area.w().saturating_sub(2 * gap_width);
-        let second_h = win_area.h().saturating_sub(first_height).saturating_sub(gap_width).saturating_sub(gap_share.0);
-        let second_area = Dimensions::new(second_x, second_y, second_w, second_h);
-        (first_area, second_area)
+        split_area(area, ratio, gap_width, false)
     }
 }
 
@@ -286,17 +402,15 @@ fn stack_clients_horizontally(area: Dimensions, clients: Vec<&Rc<RefCell<impl Cl
     }
 
     let total_gaps = (nclients - 1) * gap_width;
-    let width = (area.w().saturating_sub(total_gaps)) / nclients;
+    let widths = weighted_shares(area.w().saturating_sub(total_gaps), &clients);
     let height = area.h();
-    let width_remainder = area.w().saturating_sub(nclients * width + total_gaps);
-    for (i, client_rc) in clients.iter().enumerate() {
+    let mut x = area.x();
+    for (client_rc, width) in clients.iter().zip(widths) {
         let mut client = client_rc.borrow_mut();
         if !client.is_fullscreen() && !client.attributes().is_moving {
-            let x: i32 = area.x() + (i as i32 * (width + gap_width) as i32);
-            let y: i32 = area.y();
-            let width_adjustment = if i as u32 == nclients - 1 { width_remainder } else { 0 };
-            client.move_resize(x, y, width + width_adjustment, height);
+            client.move_resize(x, area.y(), width, height);
         }
+        x += (width + gap_width) as i32;
     }
 }
 
@@ -308,17 +422,28 @@ fn stack_clients_vertically(area: Dimensions, clients: Vec<&Rc<RefCell<impl Clie
 
     let total_gaps = (nclients - 1) * gap_width;
     let width = area.w();
-    let height = (area.h().saturating_sub(total_gaps)) / nclients;
-    let height_remainder = area.h().saturating_sub(nclients * height + total_gaps);
-    for (i, client_rc) in clients.iter().enumerate() {
+    let heights = weighted_shares(area.h().saturating_sub(total_gaps), &clients);
+    let mut y = area.y();
+    for (client_rc, height) in clients.iter().zip(heights) {
         let mut client = client_rc.borrow_mut();
         if !client.is_fullscreen() && !client.attributes().is_moving {
-            let x: i32 = area.x();
-            let y: i32 = area.y() + (i as i32 * (height + gap_width) as i32);
-            let height_adjustment = if i as u32 == nclients - 1 { height_remainder } else { 0 };
-            client.move_resize(x, y, width, height + height_adjustment);
+            client.move_resize(area.x(), y, width, height);
         }
+        y += (height + gap_width) as i32;
+    }
+}
+
+/// Split `size` between the clients according to their weights (the last client gets the remainder)
+fn weighted_shares(size: u32, clients: &[&Rc<RefCell<impl Client<Attributes>>>]) -> Vec<u32> {
+    let total_weight: f32 = clients.iter().map(|c| c.borrow().attributes().weight).sum();
+    let mut shares: Vec<u32> = clients.iter()
+        .map(|c| (size as f32 * c.borrow().attributes().weight / total_weight) as u32)
+        .collect();
+    if shares.pop().is_some() {
+        let others: u32 = shares.iter().sum();
+        shares.push(size.saturating_sub(others));
     }
+    shares
 }
 
 fn stack_clients_ontop(area: Dimensions, clients: Vec<&Rc<RefCell<impl Client<Attributes>>>>) {
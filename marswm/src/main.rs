@@ -16,12 +16,17 @@ use crate::marswm::*;
 
 mod attributes;
 mod bindings;
+mod bsp;
 mod config;
+mod generator;
+mod hooks;
 mod layouts;
 mod macros;
 mod marswm;
 mod monitor;
 mod rules;
+mod snap;
+mod switcher;
 mod workspace;
 
 
@@ -92,11 +97,6 @@ trait ClientList<C: Client<Attributes>> {
             self.attach_client(client);
         }
     }
-
-    fn transfer_all(from: &mut Self, other: &mut Self) {
-        let clients = from.detach_all();
-        other.attach_all(clients);
-    }
 }
 
 fn main() {
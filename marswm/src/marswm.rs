@@ -1,31 +1,57 @@
 use libmars::common::*;
-use libmars::platforms::x11::misc::WINDOW_MIN_SIZE;
+use libmars::platforms::x11::misc::{get_keysym, is_modifier_key, WINDOW_MIN_SIZE};
 use libmars::interfaces::wm::{ Backend, Client, WindowManager };
+use libmars::utils::configuration::read_palette;
 use std::cell::RefCell;
+use std::cmp;
+use std::collections::VecDeque;
 use std::env;
 use std::marker::PhantomData;
+use std::mem;
 use std::os::unix::process::CommandExt;
 use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
+use x11::xlib::ShiftMask;
 
 use crate::*;
 use crate::monitor::*;
 use crate::rules::*;
+use crate::snap::*;
+use crate::switcher::*;
 use crate::workspace::*;
+use crate::generator::register_layout_generators;
+use crate::hooks::*;
 use crate::layouts::LayoutType;
 
 
+/// Maximum time between two clicks of a double click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+
 pub struct MarsWM<B: Backend<Attributes>> {
     backend_phantom: PhantomData<B>,
     exec_path: PathBuf,
     config: Configuration,
     active_client: Option<Rc<RefCell<B::Client>>>,
+    focus_history: VecDeque<Rc<RefCell<B::Client>>>,  // most recently focused first
+    focus_history_pos: Option<usize>,  // position while walking through the history
+    focus_history_step: bool,  // focus is currently changed by a step of the walk
     monitors: Vec<Monitor<B::Client>>,
+    cur_monitor: usize,  // index of the selected monitor
+    monitor_pointer: Option<(i32, i32)>,  // pointer position when the monitor was selected
+    detached_monitors: Vec<DetachedMonitor<B::Client>>,  // monitors of disconnected outputs
     clients: Vec<Rc<RefCell<B::Client>>>,
     key_bindings: Vec<KeyBinding>,
+    key_prefix: Vec<(u32, u32)>,  // keys of a partially entered key sequence
+    mode: Option<String>,  // active binding mode
     button_bindings: Vec<ButtonBinding>,
+    last_click: Option<(u32, ButtonTarget, Option<u64>, Instant)>,  // used to detect double clicks
     rules: Vec<Rule>,
+    snap_preview: Option<SnapPreview>,
+    switcher: Option<WindowSwitcher<B::Client>>,
+    tiled_resize_origin: Option<ResizeOrigin>,  // layout state when resizing a tiled client started
 }
 
 impl<B: Backend<Attributes>> MarsWM<B> {
@@ -38,13 +64,26 @@ impl<B: Backend<Attributes>> MarsWM<B> {
             exec_path: env::current_exe().unwrap(),
             config,
             active_client: None,
+            focus_history: VecDeque::new(),
+            focus_history_pos: None,
+            focus_history_step: false,
             clients: Vec::new(),
             monitors: Vec::new(),
+            cur_monitor: 0,
+            monitor_pointer: None,
+            detached_monitors: Vec::new(),
             key_bindings,
+            key_prefix: Vec::new(),
+            mode: None,
             button_bindings,
-            rules
+            last_click: None,
+            rules,
+            snap_preview: None,
+            switcher: None,
+            tiled_resize_origin: None,
         };
 
+        register_layout_generators(&wm.config.layout_generators);
         let monitor_config = backend.get_monitor_config();
         (&mut wm as &mut dyn WindowManager<B, Attributes>).update_monitor_config(backend, monitor_config);
         backend.export_current_workspace(0);
@@ -54,7 +93,7 @@ impl<B: Backend<Attributes>> MarsWM<B> {
         wm
     }
 
-    pub fn apply_window_rules(&self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, app_workspace_pref: Option<u32>)
+    pub fn apply_window_rules(&self, client_rc: Rc<RefCell<B::Client>>, app_workspace_pref: Option<u32>)
             -> Option<(u32, Vec<BindingAction>, Option<WindowPlacement>)> {
         let rules: Vec<Rule> = self.rules.iter()
             .filter(|r| r.matches(client_rc.clone()))
@@ -62,7 +101,7 @@ impl<B: Backend<Attributes>> MarsWM<B> {
         let mut actions = vec![];
         let mut initial_placement = None;
 
-        let current_workspace = self.current_workspace(backend).global_index();
+        let current_workspace = self.current_workspace().global_index();
         let mut workspace = match app_workspace_pref {
             Some(ws) => ws,
             None => current_workspace,
@@ -85,12 +124,9 @@ impl<B: Backend<Attributes>> MarsWM<B> {
             }
 
             if let Some(ws) = rule.workspace() {
-                let current_monitor = self.current_monitor(backend);
-                if ws >= current_monitor.workspace_count() {
-                    workspace = current_monitor.workspace_count() - 1;
-                } else {
-                    workspace = self.current_monitor(backend).workspace_offset() + ws;
-                }
+                let current_monitor = self.current_monitor();
+                let nworkspaces = self.monitor_workspace_count(current_monitor);
+                workspace = self.monitor_workspace_idx(current_monitor, u32::min(ws, nworkspaces - 1)).unwrap();
             }
 
             actions.extend(rule.actions().iter().cloned());
@@ -106,43 +142,102 @@ impl<B: Backend<Attributes>> MarsWM<B> {
         }
     }
 
+    /// Actions of the button bindings matching a click (double click bindings take precedence)
+    fn button_actions(&self, modifiers: u32, button: u32, target: ButtonTarget, double_click: bool) -> Vec<BindingAction> {
+        let matching = |double| self.button_bindings.iter()
+            .filter(|bb| bb.matches(modifiers, button, target) && bb.double_click() == double)
+            .map(|bb| bb.action()).collect::<Vec<_>>();
+        let actions = if double_click { matching(true) } else { Vec::new() };
+        if actions.is_empty() { matching(false) } else { actions }
+    }
+
+    /// Remember a click and check whether it completes a double click (on the same window)
+    fn register_click(&mut self, button: u32, target: ButtonTarget, window: Option<u64>) -> bool {
+        let now = Instant::now();
+        let is_double_click = self.last_click
+            .is_some_and(|(b, t, w, time)| b == button && t == target && w == window && now - time <= DOUBLE_CLICK_INTERVAL);
+        self.last_click = if is_double_click { None } else { Some((button, target, window, now)) };
+        is_double_click
+    }
+
     fn clients_stacked_order(&self) -> Box<dyn Iterator<Item = &Rc<RefCell<B::Client>>> + '_> {
         let clients = self.monitors.iter().flat_map(|m| m.clients());
         Box::new(clients)
     }
 
-    fn current_monitor_index(&self, backend: &B) -> usize {
-        // TODO save last active monitor to avoid having to use the pointer (avoid backend usage)
-        let cursor_pos = backend.pointer_pos();
-        let monitor_by_pointer = self.monitors.iter().find(|m| {
-            let dims = m.config().dimensions();
-            dims.x() <= cursor_pos.0 && cursor_pos.0 < dims.x() + (dims.w() as i32)
-                && dims.y() <= cursor_pos.1 && cursor_pos.1 < dims.y() + (dims.h() as i32)
-        });
-
-        if let Some(monitor) = monitor_by_pointer {
-            self.monitors.iter().position(|m| m == monitor).unwrap()
-        } else {
-            0
-        }
+    fn current_monitor_index(&self) -> usize {
+        cmp::min(self.cur_monitor, self.monitors.len().saturating_sub(1))
     }
 
-    pub fn current_monitor(&self, backend: &B) -> &Monitor<B::Client> {
-        let index = self.current_monitor_index(backend);
+    pub fn current_monitor(&self) -> &Monitor<B::Client> {
+        let index = self.current_monitor_index();
         self.monitors.get(index).unwrap()
     }
 
-    pub fn current_monitor_mut(&mut self, backend: &B) -> &mut Monitor<B::Client> {
-        let index = self.current_monitor_index(backend);
+    pub fn current_monitor_mut(&mut self) -> &mut Monitor<B::Client> {
+        let index = self.current_monitor_index();
         self.monitors.get_mut(index).unwrap()
     }
 
-    pub fn current_workspace(&self, backend: &mut B) -> &Workspace<B::Client> {
-        self.current_monitor(backend).current_workspace()
+    pub fn current_workspace(&self) -> &Workspace<B::Client> {
+        self.current_monitor().current_workspace()
+    }
+
+    pub fn current_workspace_mut(&mut self) -> &mut Workspace<B::Client> {
+        self.current_monitor_mut().current_workspace_mut()
+    }
+
+    /// Select the monitor under the pointer if the pointer has moved since the last selection
+    ///
+    /// Moving the pointer across the root window does not generate any events, so this is checked before acting on
+    /// the selected monitor.
+    fn follow_pointer_monitor(&mut self, backend: &B) {
+        let pointer = backend.pointer_pos();
+        if self.monitor_pointer != Some(pointer)
+            && let Some(mon_idx) = self.monitor_at(pointer) {
+                self.cur_monitor = mon_idx;
+            }
+        self.monitor_pointer = Some(pointer);
+    }
+
+    fn monitor_at(&self, pos: (i32, i32)) -> Option<usize> {
+        self.monitors.iter().position(|m| m.config().dimensions().contains_point(pos))
+    }
+
+    fn monitor_index(&self, client_rc: &Rc<RefCell<B::Client>>) -> Option<usize> {
+        self.monitors.iter().position(|m| m.contains(client_rc))
+    }
+
+    /// Focus a client and move the pointer to it (depending on the `warp_pointer` setting)
+    pub fn select_client(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
+        let changes_monitor = self.monitor_index(&client_rc) != Some(self.current_monitor_index());
+        self.focus_client(backend, Some(client_rc.clone()));
+        if self.config.warp_pointer.warps(changes_monitor) {
+            client_rc.borrow().warp_pointer_to_center();
+        }
+    }
+
+    /// Select a monitor and move the pointer to it (depending on the `warp_pointer` setting)
+    fn select_monitor(&mut self, backend: &mut B, mon_idx: usize) {
+        let changes_monitor = mon_idx != self.current_monitor_index();
+        if self.config.warp_pointer.warps(changes_monitor) {
+            let (x, y) = self.monitors[mon_idx].window_area().center();
+            backend.warp_pointer(x, y);
+        }
+        self.set_current_monitor(backend, mon_idx);
     }
 
-    pub fn current_workspace_mut(&mut self, backend: &mut B) -> &mut Workspace<B::Client> {
-        self.current_monitor_mut(backend).current_workspace_mut()
+    /// Select a monitor without moving the pointer
+    fn set_current_monitor(&mut self, backend: &B, mon_idx: usize) {
+        self.cur_monitor = mon_idx;
+        self.monitor_pointer = Some(backend.pointer_pos());
+    }
+
+    pub fn set_layout(&mut self, layout: LayoutType) {
+        let workspace = self.current_workspace_mut();
+        workspace.set_layout(layout);
+        let context = HookContext::new().layout(&workspace.current_layout()).workspace(workspace);
+        run_hook(&self.config.hooks, HookEvent::LayoutChange, context);
     }
 
     pub fn switch_to_main(&mut self, backend: &mut B) {
@@ -151,7 +246,7 @@ impl<B: Backend<Attributes>> MarsWM<B> {
                 return;
             }
 
-            let ws = self.current_workspace(backend);
+            let ws = self.current_workspace();
             let client_opt = if ws.contains(active) && ws.is_main(active) {
                 ws.last_active_stack()
             } else if ws.contains(active) {
@@ -161,8 +256,8 @@ impl<B: Backend<Attributes>> MarsWM<B> {
             };
 
             if let Some(client_rc) = client_opt {
-                client_rc.borrow().warp_pointer_to_center();
-                self.current_workspace_mut(backend).raise_client(&client_rc);
+                self.current_workspace_mut().raise_client(&client_rc);
+                self.select_client(backend, client_rc);
             }
         }
     }
@@ -173,73 +268,284 @@ impl<B: Backend<Attributes>> MarsWM<B> {
                 return;
             }
 
-            let ws = self.current_workspace(backend);
+            let ws = self.current_workspace();
             let old_idx_option = ws.tiled_clients().position(|c| c == active);
-            let ws = self.current_workspace_mut(backend);
+            let ws = self.current_workspace_mut();
             if let Some(old_idx) = old_idx_option {
                 let nclients = ws.tiled_clients().count();
                 let new_idx = ((old_idx + nclients) as i32 + inc) as usize % nclients;
                 let client = ws.tiled_clients().nth(new_idx).unwrap().clone();
-                client.borrow().warp_pointer_to_center();
                 ws.raise_client(&client);
+                self.select_client(backend, client);
             }
         }
     }
 
-    pub fn cycle_monitor(&mut self, backend: &mut B, inc: i32) {
-        let current_index = self.current_monitor_index(backend);
-        let next_index = (current_index as i32 + inc) as usize % self.monitors.len();
-        if let Some(monitor) = self.monitors.get(next_index) {
-            let (x, y) = monitor.window_area().center();
-            backend.warp_pointer(x, y);
+    pub fn cycle_focus_history(&mut self, backend: &mut B, inc: i32) {
+        if self.focus_history.is_empty() {
+            return;
         }
+
+        // the history is not reordered until the walk ends
+        let len = self.focus_history.len() as i32;
+        let cur_pos = self.focus_history_pos.unwrap_or(0) as i32;
+        let new_pos = ((cur_pos + inc) % len + len) % len;
+        self.focus_history_pos = Some(new_pos as usize);
+
+        let client_rc = self.focus_history[new_pos as usize].clone();
+        self.focus_history_step = true;
+        self.activate_client(backend, client_rc);
+        self.focus_history_step = false;
+    }
+
+    pub fn cycle_layout(&mut self) {
+        let workspace = self.current_workspace_mut();
+        workspace.cycle_layout();
+        let context = HookContext::new().layout(&workspace.current_layout()).workspace(workspace);
+        run_hook(&self.config.hooks, HookEvent::LayoutChange, context);
+    }
+
+    pub fn close_window_switcher(&mut self, backend: &mut B, activate: bool) {
+        let selected = match &mut self.switcher {
+            Some(switcher) if switcher.is_open() => switcher.close(),
+            _ => return,
+        };
+        self.release_keyboard(backend);
+
+        if activate && let Some(client_rc) = selected {
+            self.activate_client(backend, client_rc);
+        }
+    }
+
+    pub fn cycle_monitor(&mut self, backend: &mut B, inc: i32) {
+        let current_index = self.current_monitor_index();
+        let next_index = (current_index as i32 + inc).rem_euclid(self.monitors.len() as i32) as usize;
+        self.focus_monitor_idx(backend, next_index, None);
     }
 
     pub fn cycle_workspace(&mut self, backend: &mut B, inc: i32) {
-        let monitor = self.current_monitor(backend);
-        let cur_workspace_idx = monitor.workspaces().position(|ws| ws == self.current_workspace(backend)).unwrap();
-        let new_workspace_idx = monitor.workspace_offset() + ((cur_workspace_idx as i32 + inc) as u32 % monitor.workspace_count());
-        self.switch_workspace(backend, new_workspace_idx);
+        let monitor = self.current_monitor();
+        let cur_workspace_idx = if self.config.shared_workspaces {
+            monitor.current_workspace().global_index()
+        } else {
+            monitor.current_workspace_idx()
+        };
+        let nworkspaces = self.monitor_workspace_count(monitor);
+        let new_idx = (cur_workspace_idx as i32 + inc).rem_euclid(nworkspaces as i32) as u32;
+        if let Some(new_workspace_idx) = self.monitor_workspace_idx(monitor, new_idx) {
+            self.switch_workspace(backend, new_workspace_idx);
+        }
     }
 
     pub fn focus_direction(&mut self, backend: &mut B, dir: Direction) {
-        if let Some(active) = self.active_client.clone() {
-            if active.borrow().is_fullscreen() {
+        let active = self.active_client.clone();
+        if let Some(active) = &active && !active.borrow().is_fullscreen() {
+            let ws = self.current_workspace_mut();
+            if let Some(selected) = ws.next_in_direction(active.clone(), dir) {
+                ws.raise_client(&selected);
+                self.select_client(backend, selected);
                 return;
             }
+        }
 
-            let ws = self.current_workspace_mut(backend);
-            if let Some(selected) = ws.next_in_direction(active, dir) {
-                selected.borrow().warp_pointer_to_center();
-                ws.raise_client(&selected);
+        // continue on the visible workspace of the neighbouring monitor
+        let mon_idx = self.current_monitor_index();
+        if let Some(target_idx) = self.monitor_in_direction(mon_idx, dir) {
+            let origin = active.map(|c| c.borrow().center())
+                .unwrap_or(self.monitors[mon_idx].dimensions().center());
+            let workspace = self.monitors[target_idx].current_workspace();
+            let selected = workspace.clients().find(|c| c.borrow().is_fullscreen())
+                .or(workspace.clients().min_by_key(|c| {
+                    let (x, y) = c.borrow().center();
+                    (x - origin.0).unsigned_abs() + (y - origin.1).unsigned_abs()
+                }))
+                .cloned();
+            self.focus_monitor_idx(backend, target_idx, selected);
+        }
+    }
+
+    pub fn focus_monitor(&mut self, backend: &mut B, dir: Direction) {
+        if let Some(mon_idx) = self.monitor_in_direction(self.current_monitor_index(), dir) {
+            self.focus_monitor_idx(backend, mon_idx, None);
+        }
+    }
+
+    /// Select a monitor and focus a client on it (the last focused client of its workspace by default)
+    fn focus_monitor_idx(&mut self, backend: &mut B, mon_idx: usize, client_option: Option<Rc<RefCell<B::Client>>>) {
+        let new_active = client_option
+            .or_else(|| self.last_focused_client(self.monitors[mon_idx].current_workspace()));
+        match new_active {
+            Some(client_rc) => self.select_client(backend, client_rc),
+            None => {
+                self.select_monitor(backend, mon_idx);
+                self.focus_client(backend, None);
+            },
+        }
+    }
+
+    /// Closest monitor in the given direction (based on the centers of the monitors)
+    fn monitor_in_direction(&self, mon_idx: usize, dir: Direction) -> Option<usize> {
+        let center = self.monitors.get(mon_idx)?.dimensions().center();
+        self.monitors.iter().enumerate()
+            .filter(|(i, _)| *i != mon_idx)
+            .filter_map(|(i, m)| dir.distance(center, m.dimensions().center()).map(|d| (i, d)))
+            .min_by_key(|(_, d)| *d)
+            .map(|(i, _)| i)
+    }
+
+    pub fn focus_from_history(&mut self, backend: &mut B, exclude: &Rc<RefCell<B::Client>>,
+                              workspace_idx: Option<u32>) {
+        let visible: Vec<_> = self.focus_history.iter()
+            .filter(|c| *c != exclude && c.borrow().is_visible())
+            .cloned().collect();
+
+        // prefer clients on the workspace that lost focus
+        let same_workspace = visible.iter()
+            .find(|c| workspace_idx.is_some() && self.get_workspace(c).map(|ws| ws.global_index()) == workspace_idx);
+        let fallback = same_workspace.or(visible.first()).cloned();
+
+        if fallback.is_some() {
+            self.focus_client(backend, fallback);
+        } else {
+            self.active_client = None;
+            backend.export_active_window(&self.active_client);
+        }
+    }
+
+    pub fn focus_last(&mut self, backend: &mut B) {
+        self.end_focus_history_walk();
+        let last = self.focus_history.iter()
+            .find(|c| Some(*c) != self.active_client.as_ref())
+            .cloned();
+        if let Some(client_rc) = last {
+            self.activate_client(backend, client_rc);
+        }
+    }
+
+    pub fn end_focus_history_walk(&mut self) {
+        if self.focus_history_pos.take().is_some()
+            && let Some(client_rc) = self.active_client.clone() {
+                self.push_focus_history(client_rc);
             }
+    }
+
+    fn push_focus_history(&mut self, client_rc: Rc<RefCell<B::Client>>) {
+        self.focus_history.retain(|c| c != &client_rc);
+        self.focus_history.push_front(client_rc);
+    }
+
+    /// Information about a client for hook commands
+    fn client_hook_context(&self, client_rc: &Rc<RefCell<B::Client>>) -> HookContext {
+        let mut context = HookContext::new().client(&*client_rc.borrow());
+        if let Some(workspace) = self.get_workspace(client_rc) {
+            context = context.workspace(workspace);
         }
+        if let Some(monitor) = self.get_monitor(client_rc) {
+            context = context.monitor(monitor.config().name());
+        }
+        context
     }
 
     pub fn decorate_active(&self, client_rc: Rc<RefCell<B::Client>>) {
         let mut client = (*client_rc).borrow_mut();
         if self.config.theming.invert_border_color {
-            client.set_inner_color(self.config.theming.inactive_color);
-            client.set_outer_color(self.config.theming.inactive_color);
+            client.set_inner_color(self.config.theming.inactive_color.pixel());
+            client.set_outer_color(self.config.theming.inactive_color.pixel());
         } else {
-            client.set_inner_color(self.config.theming.border_color);
-            client.set_outer_color(self.config.theming.border_color);
+            client.set_inner_color(self.config.theming.border_color.pixel());
+            client.set_outer_color(self.config.theming.border_color.pixel());
         }
-        client.set_title_color(self.config.theming.inactive_color);
-        client.set_frame_color(self.config.theming.active_color);
+        client.set_title_color(self.config.theming.inactive_color.pixel());
+        let frame_color = if client.is_urgent() {
+            self.config.theming.urgent_color.pixel()
+        } else {
+            self.config.theming.active_color.pixel()
+        };
+        client.set_frame_color(frame_color);
+        drop(client);
+        self.update_title(&client_rc);
     }
 
     pub fn decorate_inactive(&self, client_rc: Rc<RefCell<B::Client>>) {
         let mut client = (*client_rc).borrow_mut();
         if self.config.theming.invert_border_color {
-            client.set_inner_color(self.config.theming.active_color);
-            client.set_outer_color(self.config.theming.active_color);
+            client.set_inner_color(self.config.theming.active_color.pixel());
+            client.set_outer_color(self.config.theming.active_color.pixel());
+        } else {
+            client.set_inner_color(self.config.theming.border_color.pixel());
+            client.set_outer_color(self.config.theming.border_color.pixel());
+        }
+        client.set_title_color(self.config.theming.active_color.pixel());
+        let frame_color = if client.is_urgent() {
+            self.config.theming.urgent_color.pixel()
+        } else if client.attributes().is_pinned {
+            self.config.theming.pinned_color.pixel()
         } else {
-            client.set_inner_color(self.config.theming.border_color);
-            client.set_outer_color(self.config.theming.border_color);
+            self.config.theming.inactive_color.pixel()
+        };
+        client.set_frame_color(frame_color);
+        drop(client);
+        self.update_title(&client_rc);
+    }
+
+    /// Redecorate a client after its state has changed
+    fn redecorate(&self, client_rc: Rc<RefCell<B::Client>>) {
+        if Some(&client_rc) == self.active_client.as_ref() {
+            self.decorate_active(client_rc);
+        } else {
+            self.decorate_inactive(client_rc);
+        }
+    }
+
+    /// Expand the placeholders of the title format for a client
+    fn format_title(&self, client_rc: &Rc<RefCell<B::Client>>) -> String {
+        let client = client_rc.borrow();
+        let format = &self.config.theming.title_format;
+        let mut title = String::new();
+        let mut rest = format.as_str();
+        while let Some(start) = rest.find('{') {
+            title.push_str(&rest[..start]);
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => {
+                    rest = &rest[start..];
+                    break;
+                },
+            };
+            match &rest[start + 1..end] {
+                "class" => title.push_str(&client.application()),
+                "instance" => title.push_str(&client.instance()),
+                "title" => title.push_str(&client.title()),
+                "workspace" => if let Some(ws) = self.get_workspace(client_rc) {
+                    title.push_str(ws.name());
+                },
+                "state" => {
+                    let markers = [
+                        (client.attributes().is_floating, TitleButton::ToggleFloating),
+                        (client.attributes().is_pinned, TitleButton::Pin),
+                        (client.is_fullscreen(), TitleButton::ToggleFullscreen),
+                    ];
+                    markers.iter().filter(|(active, _)| *active)
+                        .for_each(|(_, button)| title.push_str(button.label()));
+                },
+                _ => title.push_str(&rest[start..=end]),
+            }
+            rest = &rest[end + 1..];
+        }
+        title.push_str(rest);
+        title
+    }
+
+    /// Update the text in the title bar of a client
+    fn update_title(&self, client_rc: &Rc<RefCell<B::Client>>) {
+        if self.config.theming.show_title {
+            let title = self.format_title(client_rc);
+            client_rc.borrow_mut().set_title_label(title);
         }
-        client.set_title_color(self.config.theming.active_color);
-        client.set_frame_color(self.config.theming.inactive_color);
+    }
+
+    fn update_titles(&self) {
+        self.clients.iter().for_each(|c| self.update_title(c));
     }
 
     pub fn is_tiled(&self, client_rc: &Rc<RefCell<B::Client>>) -> bool {
@@ -267,6 +573,32 @@ impl<B: Backend<Attributes>> MarsWM<B> {
             .find(|ws| ws.contains(client_rc));
     }
 
+    /// Global index of the selected workspace (indices are relative to `monitor`, names are looked up on all monitors)
+    pub fn select_workspace(&self, monitor: &Monitor<B::Client>, selector: &WorkspaceSelector) -> Option<u32> {
+        match selector {
+            WorkspaceSelector::Index(i) => self.monitor_workspace_idx(monitor, *i),
+            WorkspaceSelector::Name(name) => self.monitors.iter().flat_map(|m| m.workspaces())
+                .find(|ws| ws.name() == name)
+                .map(|ws| ws.global_index()),
+        }
+    }
+
+    pub fn enter_mode(&mut self, backend: &mut B, mode: &str) {
+        if self.mode.is_none() && !backend.grab_keyboard() {
+            return;
+        }
+
+        self.mode = Some(mode.to_owned());
+        backend.export_mode(mode);
+    }
+
+    pub fn exit_mode(&mut self, backend: &mut B) {
+        if self.mode.take().is_some() {
+            backend.export_mode("");
+            self.release_keyboard(backend);
+        }
+    }
+
     pub fn exit(&mut self, backend: &mut B) {
         println!("Shutting down");
         self.cleanup(backend);
@@ -274,12 +606,43 @@ impl<B: Backend<Attributes>> MarsWM<B> {
         process::exit(0);
     }
 
-    pub fn mouse_action_place(_backend: &mut B, wm: &mut Self, client_rc: &Rc<RefCell<B::Client>>,
-                         orig_client_pos: (i32, i32), _orig_client_size: (u32, u32), delta: (i32, i32)) {
-        let dest_x = orig_client_pos.0 + delta.0;
-        let dest_y = orig_client_pos.1 + delta.1;
-        let size = client_rc.borrow().size();
+    pub fn minimize_client(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
+        match self.get_workspace_mut(&client_rc) {
+            Some(ws) if !ws.is_minimized(&client_rc) => ws.minimize(client_rc.clone()),
+            _ => return,
+        }
+
+        self.decorate_inactive(client_rc.clone());
+        client_rc.borrow_mut().hide();
+        if Some(&client_rc) == self.active_client.as_ref() {
+            self.active_client = None;
+            let workspace_idx = self.active_workspace(backend);
+            self.focus_from_history(backend, &client_rc, Some(workspace_idx));
+        }
+    }
+
+    pub fn mouse_action_move(backend: &mut B, wm: &mut Self, client_rc: &Rc<RefCell<B::Client>>,
+                         orig_client_pos: (i32, i32), orig_client_size: (u32, u32), delta: (i32, i32)) {
+        let (dest_x, dest_y, size) = wm.floating_drag_destination(backend, client_rc, orig_client_pos,
+                                                                  orig_client_size, delta);
+        client_rc.borrow_mut().move_resize(dest_x, dest_y, size.0, size.1);
+        wm.update_snap_preview(backend);
+    }
+
+    pub fn mouse_action_place(backend: &mut B, wm: &mut Self, client_rc: &Rc<RefCell<B::Client>>,
+                         orig_client_pos: (i32, i32), orig_client_size: (u32, u32), delta: (i32, i32)) {
+        // only floating windows stick to edges and snap zones, tiled ones are just being dragged to a new slot
+        let is_floating = is_floating!(wm, client_rc);
+        let (dest_x, dest_y, size) = if is_floating {
+            wm.floating_drag_destination(backend, client_rc, orig_client_pos, orig_client_size, delta)
+        } else {
+            (orig_client_pos.0 + delta.0, orig_client_pos.1 + delta.1, client_rc.borrow().size())
+        };
+
         client_rc.borrow_mut().move_resize(dest_x, dest_y, size.0, size.1);
+        if is_floating {
+            wm.update_snap_preview(backend);
+        }
         let client_center = client_rc.borrow().center();
 
         if let Some(workspace) = wm.get_workspace_mut(client_rc) {
@@ -294,6 +657,42 @@ impl<B: Backend<Attributes>> MarsWM<B> {
         }
     }
 
+    /// Calculate position and size of a dragged floating window
+    fn floating_drag_destination(&self, backend: &mut B, client_rc: &Rc<RefCell<B::Client>>, orig_client_pos: (i32, i32),
+                                 orig_client_size: (u32, u32), delta: (i32, i32)) -> (i32, i32, (u32, u32)) {
+        let mut dest_x = orig_client_pos.0 + delta.0;
+        let mut dest_y = orig_client_pos.1 + delta.1;
+
+        // windows snapped to a zone get their previous size back as soon as they are dragged
+        let restored = client_rc.borrow_mut().attributes_mut().unsnapped_dimensions.take();
+        if let Some(dimensions) = restored {
+            let pos = client_rc.borrow().pos();
+            client_rc.borrow_mut().move_resize(pos.0, pos.1, dimensions.w(), dimensions.h());
+        }
+        let size = client_rc.borrow().size();
+        if size.0 != orig_client_size.0 {
+            // keep the pointer at the same relative position inside the window
+            let pointer_x = backend.pointer_pos().0;
+            let offset = (pointer_x - delta.0 - orig_client_pos.0) as i64 * size.0 as i64 / orig_client_size.0 as i64;
+            dest_x = pointer_x - offset as i32;
+        }
+
+        if self.config.edge_resistance > 0 {
+            let areas: Vec<Dimensions> = self.monitors.iter()
+                .flat_map(|m| [m.config().dimensions(), m.window_area()])
+                .collect();
+            let windows: Vec<Dimensions> = self.get_workspace(client_rc).into_iter()
+                .flat_map(|ws| ws.clients())
+                .filter(|c| *c != client_rc)
+                .map(|c| c.borrow().dimensions())
+                .collect();
+            (dest_x, dest_y) = Dimensions::new(dest_x, dest_y, size.0, size.1)
+                .snap_position(&areas, &windows, self.config.edge_resistance);
+        }
+
+        (dest_x, dest_y, size)
+    }
+
     pub fn mouse_action_resize_centered(_backend: &mut B, _wm: &mut Self, client_rc: &Rc<RefCell<B::Client>>,
                          orig_client_pos: (i32, i32), orig_client_size: (u32, u32), delta: (i32, i32)) {
         let dest_w = orig_client_size.0 as i32 + 2 * delta.0;
@@ -306,6 +705,36 @@ impl<B: Backend<Attributes>> MarsWM<B> {
     }
 
 
+    pub fn mouse_action_resize_split(backend: &mut B, wm: &mut Self, client_rc: &Rc<RefCell<B::Client>>,
+                         _orig_client_pos: (i32, i32), _orig_client_size: (u32, u32), _delta: (i32, i32)) {
+        let pointer = backend.pointer_pos();
+        if let Some(ws) = wm.get_workspace_mut(client_rc) {
+            ws.set_split_position(client_rc, pointer);
+        }
+    }
+
+    pub fn mouse_action_resize_tiled(_backend: &mut B, wm: &mut Self, client_rc: &Rc<RefCell<B::Client>>,
+                         _orig_client_pos: (i32, i32), orig_client_size: (u32, u32), delta: (i32, i32)) {
+        if let Some(origin) = wm.tiled_resize_origin
+            && let Some(ws) = wm.get_workspace_mut(client_rc) {
+                ws.resize_tiled(client_rc, origin, orig_client_size, delta);
+            }
+    }
+
+    pub fn mouse_move(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
+        let sticks = self.config.edge_resistance > 0 || self.config.snap_zone_width > 0;
+        if !is_floating!(self, &client_rc) || !sticks {
+            backend.mouse_move(self, client_rc);
+            return;
+        }
+        if client_rc.borrow().is_fullscreen() {
+            return;
+        }
+
+        backend.mouse_action(self, client_rc.clone(), 52, Self::mouse_action_move);
+        self.finish_snap(backend, &client_rc);
+    }
+
     pub fn mouse_place(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
         let mut client = client_rc.borrow_mut();
         let was_fullscreen = client.is_fullscreen();
@@ -317,116 +746,765 @@ impl<B: Backend<Attributes>> MarsWM<B> {
         client.raise();
         drop(client);
 
+        let is_floating = is_floating!(self, &client_rc);
         backend.mouse_action(self, client_rc.clone(), 52, Self::mouse_action_place);
+        if is_floating {
+            self.finish_snap(backend, &client_rc);
+        }
+
+        client_rc.borrow_mut().attributes_mut().is_moving = false;
+        if let Some(ws) = self.get_workspace_mut(&client_rc) {
+            ws.drop_fullscreen();
+            ws.restack()
+        }
+
+        if was_fullscreen
+            && let Some(mon) = self.get_monitor(&client_rc) {
+                client_rc.borrow_mut().set_fullscreen(mon.config());
+            }
+    }
+
+    /// Resize a tiled client with the mouse
+    ///
+    /// In the bsp layout this drags the border of the split containing the client,
+    /// in other layouts the border between main and stack area or to the adjacent client in the same stack.
+    pub fn mouse_resize_tiled(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
+        let layout = match self.get_workspace(&client_rc) {
+            Some(ws) => ws.current_layout(),
+            None => return,
+        };
+        if client_rc.borrow().is_fullscreen() {
+            return;
+        }
+
+        if layout == LayoutType::Bsp {
+            backend.mouse_action(self, client_rc, 120, Self::mouse_action_resize_split);
+        } else {
+            let pointer = backend.pointer_pos();
+            self.tiled_resize_origin = self.get_workspace(&client_rc)
+                .map(|ws| ws.resize_origin(&client_rc, pointer));
+            backend.mouse_action(self, client_rc, 120, Self::mouse_action_resize_tiled);
+            self.tiled_resize_origin = None;
+        }
+    }
+
+    pub fn mouse_resize_centered(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
+        let client = client_rc.borrow_mut();
+        if client.is_fullscreen() {
+            return;
+        }
+
+        client.warp_pointer_to_corner();
+        client.raise();
+        drop(client);
+
+        backend.mouse_action(self, client_rc.clone(), 120, Self::mouse_action_resize_centered);
+
+        client_rc.borrow_mut().attributes_mut().is_moving = false;
+        self.current_workspace_mut().restack();
+    }
+
+    pub fn move_client_to_monitor(&mut self, client_rc: Rc<RefCell<B::Client>>, inc: i32) {
+        let client_index = if let Some(ci) = self.monitor_index(&client_rc) { ci } else { return };
+        let target_index = (client_index as i32 + inc).rem_euclid(self.monitors.len() as i32) as usize;
+        self.move_client_to_monitor_idx(client_rc, client_index, target_index);
+    }
+
+    pub fn move_client_to_monitor_in_direction(&mut self, client_rc: Rc<RefCell<B::Client>>, dir: Direction) {
+        let client_index = if let Some(ci) = self.monitor_index(&client_rc) { ci } else { return };
+        if let Some(target_index) = self.monitor_in_direction(client_index, dir) {
+            self.move_client_to_monitor_idx(client_rc, client_index, target_index);
+        }
+    }
+
+    fn move_client_to_monitor_idx(&mut self, client_rc: Rc<RefCell<B::Client>>, client_index: usize, target_index: usize) {
+        self.monitors[client_index].detach_client(&client_rc);
+        Self::fix_client_to_area(client_rc.clone(), self.monitors[target_index].window_area());
+        self.monitors[target_index].attach_client(client_rc.clone());
+
+        if client_rc.borrow().is_fullscreen() {
+            client_rc.borrow_mut().set_fullscreen(self.monitors[target_index].config())
+        }
+    }
+
+    /// Snap a window dropped in a snap zone and hide the preview
+    fn finish_snap(&mut self, backend: &mut B, client_rc: &Rc<RefCell<B::Client>>) {
+        if let Some(preview) = &mut self.snap_preview {
+            preview.hide();
+        }
+
+        if let Some((monitor_index, zone)) = self.snap_zone(backend) {
+            let area = zone.area(self.monitors[monitor_index].window_area());
+            Self::save_unsnapped_dimensions(client_rc);
+            client_rc.borrow_mut().set_dimensions(area);
+
+            if self.get_monitor(client_rc) != Some(&self.monitors[monitor_index]) {
+                self.client_switches_monitor(client_rc.clone(), monitor_index as u32);
+            }
+        }
+    }
+
+    /// Remember the geometry of a floating window before it gets snapped
+    fn save_unsnapped_dimensions(client_rc: &Rc<RefCell<B::Client>>) {
+        let mut client = client_rc.borrow_mut();
+        if client.attributes().unsnapped_dimensions.is_none() {
+            let dimensions = client.dimensions();
+            client.attributes_mut().unsnapped_dimensions = Some(dimensions);
+        }
+    }
+
+    /// Find the monitor and snap zone below the pointer
+    fn snap_zone(&self, backend: &B) -> Option<(usize, SnapZone)> {
+        if self.config.snap_zone_width == 0 {
+            return None;
+        }
+
+        let pointer = backend.pointer_pos();
+        let monitor_index = self.monitors.iter().position(|m| m.config().contains_point(pointer))?;
+        SnapZone::at(self.monitors[monitor_index].config().dimensions(), pointer, self.config.snap_zone_width)
+            .map(|zone| (monitor_index, zone))
+    }
+
+    pub fn snap_window(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, dir: Direction) {
+        if !is_floating!(self, &client_rc) {
+            self.swap_direction(backend, dir);
+            return;
+        }
+        if client_rc.borrow().is_fullscreen() {
+            return;
+        }
+        let area = match self.get_monitor(&client_rc) {
+            Some(monitor) => monitor.window_area(),
+            None => return,
+        };
+
+        let current = client_rc.borrow().dimensions();
+        let dimensions = match dir {
+            Direction::Down => {
+                let restored = client_rc.borrow_mut().attributes_mut().unsnapped_dimensions.take();
+                match restored {
+                    Some(dimensions) => Self::clamp_to_area(dimensions, area),
+                    None => return,
+                }
+            },
+            Direction::Up => {
+                Self::save_unsnapped_dimensions(&client_rc);
+                area
+            },
+            Direction::Left | Direction::Right => {
+                Self::save_unsnapped_dimensions(&client_rc);
+                next_snap_width(current, area, dir)
+            },
+        };
+        client_rc.borrow_mut().set_dimensions(dimensions);
+    }
+
+    fn update_snap_preview(&mut self, backend: &mut B) {
+        let zone_area = self.snap_zone(backend)
+            .map(|(monitor_index, zone)| zone.area(self.monitors[monitor_index].window_area()));
+
+        if zone_area.is_some() && self.snap_preview.is_none() {
+            match SnapPreview::new(&self.config.theming) {
+                Ok(preview) => self.snap_preview = Some(preview),
+                Err(e) => eprintln!("Unable to create snap preview: {}", e),
+            }
+        }
+
+        if let Some(preview) = &mut self.snap_preview {
+            match zone_area {
+                Some(area) => preview.show(area),
+                None => preview.hide(),
+            }
+        }
+    }
+
+    pub fn move_floating(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, dx: Length, dy: Length) {
+        if !is_floating!(self, &client_rc) {
+            let area = self.current_monitor().window_area();
+            if let Some(dir) = Direction::from_delta(dx.resolve(area.w()), dy.resolve(area.h())) {
+                self.swap_direction(backend, dir);
+            }
+            return;
+        }
+        if client_rc.borrow().is_fullscreen() {
+            return;
+        }
+
+        let monitor_index = match self.get_monitor(&client_rc).and_then(|cm| self.monitors.iter().position(|m| m == cm)) {
+            Some(index) => index,
+            None => return,
+        };
+        let monitor_dimensions = self.monitors[monitor_index].config().dimensions();
+        let area = self.monitors[monitor_index].window_area();
+        let (dx, dy) = (dx.resolve(area.w()), dy.resolve(area.h()));
+        let orig_dimensions = client_rc.borrow().dimensions();
+        let mut dimensions = orig_dimensions;
+        dimensions.set_pos((orig_dimensions.x() + dx, orig_dimensions.y() + dy));
+
+        // windows that already touch an edge are carried over to the neighbouring monitor
+        let (center_x, center_y) = orig_dimensions.center();
+        let beyond_edge = if dx > 0 && orig_dimensions.right() >= area.right() {
+            Some((monitor_dimensions.right(), center_y))
+        } else if dx < 0 && orig_dimensions.x() <= area.x() {
+            Some((monitor_dimensions.x() - 1, center_y))
+        } else if dy > 0 && orig_dimensions.bottom() >= area.bottom() {
+            Some((center_x, monitor_dimensions.bottom()))
+        } else if dy < 0 && orig_dimensions.y() <= area.y() {
+            Some((center_x, monitor_dimensions.y() - 1))
+        } else {
+            None
+        };
+        let target_index = beyond_edge
+            .and_then(|point| self.monitors.iter().position(|m| m.config().contains_point(point)));
+
+        if let Some(target_index) = target_index {
+            let target_area = self.monitors[target_index].window_area();
+            if dx > 0 {
+                dimensions.set_x(target_area.x());
+            } else if dx < 0 {
+                dimensions.set_x(target_area.right() - dimensions.w() as i32);
+            } else if dy > 0 {
+                dimensions.set_y(target_area.y());
+            } else {
+                dimensions.set_y(target_area.bottom() - dimensions.h() as i32);
+            }
+
+            self.monitors[monitor_index].detach_client(&client_rc);
+            self.monitors[target_index].attach_client(client_rc.clone());
+            client_rc.borrow_mut().set_dimensions(Self::clamp_to_area(dimensions, target_area));
+        } else {
+            client_rc.borrow_mut().set_dimensions(Self::clamp_to_area(dimensions, area));
+        }
+    }
+
+    pub fn resize_floating(&mut self, client_rc: Rc<RefCell<B::Client>>, dw: Length, dh: Length) {
+        let area = match self.get_monitor(&client_rc) {
+            Some(monitor) => monitor.window_area(),
+            None => return,
+        };
+        let (dw, dh) = (dw.resolve(area.w()), dh.resolve(area.h()));
+
+        if !is_floating!(self, &client_rc) {
+            let ratio = if dw != 0 { dw as f32 / area.w() as f32 } else { dh as f32 / area.h() as f32 };
+            self.current_workspace_mut().change_main_ratio(ratio);
+            return;
+        }
+        if client_rc.borrow().is_fullscreen() {
+            return;
+        }
+
+        client_rc.borrow_mut().attributes_mut().unsnapped_dimensions = None;
+        let mut dimensions = client_rc.borrow().dimensions();
+        dimensions.set_w(cmp::max(dimensions.w() as i32 + dw, WINDOW_MIN_SIZE as i32) as u32);
+        dimensions.set_h(cmp::max(dimensions.h() as i32 + dh, WINDOW_MIN_SIZE as i32) as u32);
+        client_rc.borrow_mut().set_dimensions(Self::clamp_to_area(dimensions, area));
+    }
+
+    pub fn resize_floating_to(&mut self, client_rc: Rc<RefCell<B::Client>>, w: Length, h: Length) {
+        if !is_floating!(self, &client_rc) || client_rc.borrow().is_fullscreen() {
+            return;
+        }
+        let area = match self.get_monitor(&client_rc) {
+            Some(monitor) => monitor.window_area(),
+            None => return,
+        };
+
+        client_rc.borrow_mut().attributes_mut().unsnapped_dimensions = None;
+        let mut dimensions = client_rc.borrow().dimensions();
+        let (center_x, center_y) = dimensions.center();
+        dimensions.set_w(cmp::max(w.resolve(area.w()), WINDOW_MIN_SIZE as i32) as u32);
+        dimensions.set_h(cmp::max(h.resolve(area.h()), WINDOW_MIN_SIZE as i32) as u32);
+        dimensions.set_pos((center_x - dimensions.w() as i32 / 2, center_y - dimensions.h() as i32 / 2));
+        client_rc.borrow_mut().set_dimensions(Self::clamp_to_area(dimensions, area));
+    }
+
+    pub fn snap_to_edge(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, dir: Direction) {
+        if !is_floating!(self, &client_rc) {
+            self.swap_direction(backend, dir);
+            return;
+        }
+        if client_rc.borrow().is_fullscreen() {
+            return;
+        }
+        let area = match self.get_monitor(&client_rc) {
+            Some(monitor) => monitor.window_area(),
+            None => return,
+        };
+
+        let mut dimensions = client_rc.borrow().dimensions();
+        match dir {
+            Direction::Left => dimensions.set_x(area.x()),
+            Direction::Right => dimensions.set_x(area.right() - dimensions.w() as i32),
+            Direction::Up => dimensions.set_y(area.y()),
+            Direction::Down => dimensions.set_y(area.bottom() - dimensions.h() as i32),
+        }
+        client_rc.borrow_mut().set_dimensions(Self::clamp_to_area(dimensions, area));
+    }
+
+    /// Shrink and move dimensions so they fit into the area
+    fn clamp_to_area(mut dimensions: Dimensions, area: Dimensions) -> Dimensions {
+        dimensions.set_w(cmp::min(dimensions.w(), area.w()));
+        dimensions.set_h(cmp::min(dimensions.h(), area.h()));
+        dimensions.set_x(cmp::max(cmp::min(dimensions.x(), area.right() - dimensions.w() as i32), area.x()));
+        dimensions.set_y(cmp::max(cmp::min(dimensions.y(), area.bottom() - dimensions.h() as i32), area.y()));
+        dimensions
+    }
+
+    fn fix_client_to_area(client_rc: Rc<RefCell<B::Client>>, area: Dimensions) {
+        let client_dimensions_orig = client_rc.borrow().dimensions();
+        let mut client_dimensions = client_dimensions_orig;
+
+        if client_dimensions.x() < area.x() {
+            client_dimensions.set_x(area.x());
+        }
+        if client_dimensions.y() < area.y() {
+            client_dimensions.set_y(area.y());
+        }
+        if client_dimensions.right() > area.right() {
+            client_dimensions.set_x(area.right() - client_dimensions.w() as i32);
+        }
+        if client_dimensions.bottom() > area.bottom() {
+            client_dimensions.set_y(area.bottom() - client_dimensions.h() as i32);
+        }
+
+        if client_dimensions != client_dimensions_orig {
+            // center window if it's completely off
+            if !area.contains_point(client_dimensions_orig.center()) {
+                client_rc.borrow_mut().center_on_screen(area);
+            } else {
+                client_rc.borrow_mut().set_dimensions(client_dimensions);
+            }
+        }
+    }
+
+    fn reset_key_prefix(&mut self, backend: &mut B) {
+        if self.key_prefix.is_empty() {
+            return;
+        }
+
+        self.key_prefix.clear();
+        backend.set_timeout(None);
+        backend.export_key_prefix("");
+        self.release_keyboard(backend);
+    }
+
+    /// Release the keyboard grab unless it is still needed by a binding mode, key sequence or the window switcher
+    fn release_keyboard(&self, backend: &mut B) {
+        if !self.grabs_keyboard() {
+            backend.ungrab_keyboard();
+        }
+    }
+
+    fn grabs_keyboard(&self) -> bool {
+        self.mode.is_some() || !self.key_prefix.is_empty()
+            || self.switcher.as_ref().is_some_and(|s| s.is_open())
+    }
+
+    /// Find the monitor and the position on that monitor of a workspace
+    fn relative_workspace_idx(&self, absolute_idx: u32) -> (usize, u32) {
+        for (mon_idx, monitor) in self.monitors.iter().enumerate() {
+            if let Some(rel_idx) = monitor.workspaces().position(|ws| ws.global_index() == absolute_idx) {
+                return (mon_idx, rel_idx as u32);
+            }
+        }
+        (self.monitors.len(), absolute_idx)
+    }
+
+    /// Number of workspaces that can be selected from a monitor (all workspaces when they are shared)
+    fn monitor_workspace_count(&self, monitor: &Monitor<B::Client>) -> u32 {
+        if self.config.shared_workspaces {
+            self.workspace_count()
+        } else {
+            monitor.workspace_count()
+        }
+    }
+
+    /// Global index of the workspace with the given index on a monitor (indices are global for shared workspaces)
+    fn monitor_workspace_idx(&self, monitor: &Monitor<B::Client>, index: u32) -> Option<u32> {
+        if self.config.shared_workspaces {
+            (index < self.workspace_count()).then_some(index)
+        } else {
+            monitor.workspace(index).map(|ws| ws.global_index())
+        }
+    }
+
+    /// Add a workspace to the current monitor (either at the end or in front of the current workspace)
+    pub fn add_workspace(&mut self, backend: &mut B, before_current: bool) {
+        let mon_idx = self.current_monitor_index();
+        let monitor = &self.monitors[mon_idx];
+        let (index, global_index) = if before_current {
+            (monitor.current_workspace_idx(), monitor.current_workspace().global_index())
+        } else if self.config.shared_workspaces {
+            (monitor.workspace_count(), self.workspace_count())
+        } else {
+            (monitor.workspace_count(), monitor.workspace_offset() + monitor.workspace_count())
+        };
+
+        self.shift_workspace_indices(global_index, 1);
+        self.monitors[mon_idx].insert_workspace(&self.config, index, global_index);
+        self.update_workspace_indices(backend);
+    }
+
+    /// Move the workspaces of the given index and above by `inc`
+    fn shift_workspace_indices(&mut self, from: u32, inc: i32) {
+        for workspace in self.monitors.iter_mut().flat_map(|m| m.workspaces_mut()) {
+            if workspace.global_index() >= from {
+                workspace.set_global_index((workspace.global_index() as i32 + inc) as u32);
+            }
+        }
+    }
+
+    pub fn rename_workspace(&mut self, backend: &mut B, workspace_idx: u32, name: String) {
+        let (mon_idx, rel_idx) = self.relative_workspace_idx(workspace_idx);
+        if let Some(workspace) = self.monitors.get_mut(mon_idx).and_then(|m| m.workspace_mut(rel_idx)) {
+            workspace.rename(name);
+            self.export_workspaces(backend);
+            self.update_titles();
+        }
+    }
+
+    fn export_workspaces(&self, backend: &mut B) {
+        let mut workspaces: Vec<_> = self.monitors.iter()
+            .flat_map(|m| m.workspaces().map(move |ws| (ws, m)))
+            .collect();
+        workspaces.sort_by_key(|(ws, _)| ws.global_index());
+        let workspace_info = workspaces.iter()
+            .map(|(ws, m)| (ws.label(), m.dimensions(), m.window_area()))
+            .collect();
+        backend.export_workspaces(workspace_info);
+    }
+
+    /// Export workspace information after workspaces have been added, removed or moved
+    fn update_workspace_indices(&mut self, backend: &mut B) {
+        // workspaces are numbered by monitor unless they are shared
+        if !self.config.shared_workspaces {
+            let mut offset = 0;
+            for monitor in &mut self.monitors {
+                for workspace in monitor.workspaces_mut() {
+                    workspace.set_global_index(offset);
+                    offset += 1;
+                }
+            }
+        }
+
+        self.export_workspaces(backend);
+        let ws = self.active_workspace(backend);
+        backend.export_current_workspace(ws);
+    }
+
+    fn workspace_count(&self) -> u32 {
+        self.monitors.iter().map(|m| m.workspace_count()).sum()
+    }
+
+    pub fn restart(&mut self, backend: &mut B) {
+        println!("Restarting");
+        self.cleanup(backend);
+        backend.shutdown();
+
+        // get args without exec_path
+        let args = env::args().skip(1);
+        eprintln!("Path: {:?}", self.exec_path);
+        eprintln!("Args: {:?}", args);
+
+        let mut command = process::Command::new(self.exec_path.clone());
+        let command = command.args(args);
+        let error = command.exec();
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+
+    pub fn swap_direction(&mut self, backend: &mut B, dir: Direction) {
+        if let Some(active) = self.active_client.clone() {
+            let ws = self.current_workspace_mut();
+            if let Some(selected) = ws.next_in_direction(active.clone(), dir) {
+                ws.swap_clients(active.clone(), selected);
+                self.select_client(backend, active);
+            }
+        }
+    }
+
+    pub fn window_switcher(&mut self, backend: &mut B, inc: i32) {
+        if let Some(switcher) = &mut self.switcher && switcher.is_open() {
+            switcher.cycle(inc);
+            return;
+        }
+
+        if self.switcher.is_none() {
+            match WindowSwitcher::new(&self.config.theming) {
+                Ok(switcher) => self.switcher = Some(switcher),
+                Err(e) => {
+                    eprintln!("Unable to create window switcher: {}", e);
+                    return;
+                },
+            }
+        }
 
-        client_rc.borrow_mut().attributes_mut().is_moving = false;
-        if let Some(ws) = self.get_workspace_mut(&client_rc) {
-            ws.drop_fullscreen();
-            ws.restack()
+        // most recently used clients first, clients that have never been focused last
+        let entries: Vec<_> = self.focus_history.iter()
+            .chain(self.clients.iter().filter(|c| !self.focus_history.contains(c)))
+            .map(|c| {
+                let workspace = self.get_workspace(c).map(|ws| ws.name().to_owned())
+                    .unwrap_or("*".to_owned());
+                (c.clone(), workspace)
+            }).collect();
+        if entries.is_empty() || !backend.grab_keyboard() {
+            return;
         }
 
-        if was_fullscreen
-            && let Some(mon) = self.get_monitor(&client_rc) {
-                client_rc.borrow_mut().set_fullscreen(mon.config());
+        let area = self.current_monitor().window_area();
+        if let Some(switcher) = &mut self.switcher {
+            switcher.open(entries, area);
+            if inc != 1 {
+                switcher.cycle(inc - 1);
             }
+        }
     }
 
-    pub fn mouse_resize_centered(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
-        let client = client_rc.borrow_mut();
-        if client.is_fullscreen() {
+    pub fn switch_prev_workspace(&mut self, backend: &mut B) {
+        let prev_index = self.current_monitor().prev_workspace().global_index();
+        self.switch_workspace(backend, prev_index);
+    }
+
+    /// Make a workspace the current workspace of its monitor (without focusing it)
+    fn show_workspace(&mut self, mon_idx: usize, rel_idx: u32) {
+        if self.monitors[mon_idx].current_workspace_idx() == rel_idx {
             return;
         }
 
-        client.warp_pointer_to_corner();
-        client.raise();
-        drop(client);
+        // take pinned clients from old workspace and hide other clients
+        let from_workspace = self.monitors[mon_idx].current_workspace_mut();
+        let pinned_clients = from_workspace.pull_pinned();
 
-        backend.mouse_action(self, client_rc.clone(), 120, Self::mouse_action_resize_centered);
+        // redecorate and hide old clients
+        for client_rc in from_workspace.clients().cloned().collect::<Vec<_>>() {
+            self.decorate_inactive(client_rc.clone());
+            client_rc.borrow_mut().hide();
+        }
 
-        client_rc.borrow_mut().attributes_mut().is_moving = false;
-        self.current_workspace_mut(backend).restack();
+        // set workspace index to new workspace
+        self.monitors[mon_idx].set_cur_workspace(rel_idx);
+
+        // attach pinned clients to new workspace and show clients
+        let to_workspace = self.monitors[mon_idx].current_workspace_mut();
+        to_workspace.push_pinned(pinned_clients);
+        to_workspace.clients().for_each(|c| c.borrow_mut().show());
     }
 
-    pub fn move_client_to_monitor(&mut self, client_rc: Rc<RefCell<B::Client>>, inc: i32) {
-        let client_index_opt = self.get_monitor(&client_rc).and_then(|cm| self.monitors.iter().position(|m| m == cm));
-        let client_index = if let Some(ci) = client_index_opt { ci } else { return };
-        let target_index = (client_index as i32 + inc) as usize % self.monitors.len();
+    /// Focus the current workspace of a monitor
+    fn focus_workspace(&mut self, backend: &mut B, mon_idx: usize) {
+        // select new window to be focused
+        let to_workspace = self.monitors[mon_idx].current_workspace();
+        let new_active = self.last_focused_client(to_workspace);
+        let workspace_idx = to_workspace.global_index();
+        self.set_current_monitor(backend, mon_idx);
+        self.focus_client(backend, new_active);
 
-        self.monitors[client_index].detach_client(&client_rc);
-        Self::fix_client_to_area(client_rc.clone(), self.monitors[target_index].window_area());
-        self.monitors[target_index].attach_client(client_rc.clone());
+        backend.export_current_workspace(workspace_idx);
 
-        if client_rc.borrow().is_fullscreen() {
-            client_rc.borrow_mut().set_fullscreen(self.monitors[target_index].config())
-        }
+        let monitor = &self.monitors[mon_idx];
+        let context = HookContext::new().workspace(monitor.current_workspace()).monitor(monitor.config().name());
+        run_hook(&self.config.hooks, HookEvent::WorkspaceSwitch, context);
     }
 
-    fn fix_client_to_area(client_rc: Rc<RefCell<B::Client>>, area: Dimensions) {
-        let client_dimensions_orig = client_rc.borrow().dimensions();
-        let mut client_dimensions = client_dimensions_orig;
+    /// Client that should be focused when a workspace is focused
+    fn last_focused_client(&self, workspace: &Workspace<B::Client>) -> Option<Rc<RefCell<B::Client>>> {
+        if let Some(client_rc) = workspace.clients().find(|c| c.borrow().is_fullscreen()) {
+            Some(client_rc.clone())
+        } else if let Some(client_rc) = self.focus_history.iter().find(|c| workspace.contains(c)) {
+            Some(client_rc.clone())
+        } else { workspace.clients().next().cloned() }
+    }
 
-        if client_dimensions.x() < area.x() {
-            client_dimensions.set_x(area.x());
-        }
-        if client_dimensions.y() < area.y() {
-            client_dimensions.set_y(area.y());
-        }
-        if client_dimensions.right() > area.right() {
-            client_dimensions.set_x(area.right() - client_dimensions.w() as i32);
+    /// Swap two workspaces between their positions on (possibly different) monitors
+    fn exchange_workspaces(&mut self, backend: &mut B, (mon_a, rel_a): (usize, u32), (mon_b, rel_b): (usize, u32)) {
+        if mon_a == mon_b {
+            return;
         }
-        if client_dimensions.bottom() > area.bottom() {
-            client_dimensions.set_y(area.bottom() - client_dimensions.h() as i32);
+
+        let [monitor_a, monitor_b] = match self.monitors.get_disjoint_mut([mon_a, mon_b]) {
+            Ok(monitors) => monitors,
+            Err(_) => return,
+        };
+        let (config_a, config_b) = (monitor_a.config().clone(), monitor_b.config().clone());
+        let (visible_a, visible_b) = (monitor_a.current_workspace_idx() == rel_a, monitor_b.current_workspace_idx() == rel_b);
+        let (workspace_a, workspace_b) = match (monitor_a.workspace_mut(rel_a), monitor_b.workspace_mut(rel_b)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return,
+        };
+
+        // pinned clients stay on their monitor
+        let pinned_a = workspace_a.pull_pinned();
+        let pinned_b = workspace_b.pull_pinned();
+        mem::swap(workspace_a, workspace_b);
+        Self::relocate_workspace(workspace_a, &config_b, &config_a);
+        Self::relocate_workspace(workspace_b, &config_a, &config_b);
+        workspace_a.push_pinned(pinned_a);
+        workspace_b.push_pinned(pinned_b);
+
+        for (workspace, visible) in [(&*workspace_a, visible_a), (&*workspace_b, visible_b)] {
+            for client_rc in workspace.clients() {
+                if visible {
+                    client_rc.borrow_mut().show();
+                } else {
+                    client_rc.borrow_mut().hide();
+                }
+            }
         }
 
-        if client_dimensions != client_dimensions_orig {
-            // center window if it's completely off
-            if !area.contains_point(client_dimensions_orig.center()) {
-                client_rc.borrow_mut().center_on_screen(area);
+        self.export_workspaces(backend);
+    }
+
+    /// Adjust the clients of a workspace that is moved from one monitor to another
+    fn relocate_workspace(workspace: &mut Workspace<B::Client>, from: &MonitorConfig, to: &MonitorConfig) {
+        let (from_area, to_area) = (from.window_area(), to.window_area());
+        for client_rc in workspace.clients() {
+            if client_rc.borrow().is_fullscreen() {
+                client_rc.borrow_mut().set_fullscreen(to);
             } else {
-                client_rc.borrow_mut().set_dimensions(client_dimensions);
+                let mut dimensions = client_rc.borrow().dimensions();
+                dimensions.set_x(dimensions.x() - from_area.x() + to_area.x());
+                dimensions.set_y(dimensions.y() - from_area.y() + to_area.y());
+                client_rc.borrow_mut().set_dimensions(dimensions);
+                Self::fix_client_to_area(client_rc.clone(), to_area);
             }
         }
+        workspace.update_window_area(to_area);
     }
 
-    fn relative_workspace_idx(&self, absolute_idx: u32) -> (usize, u32) {
-        if absolute_idx < self.config.primary_workspaces {
-            (0, absolute_idx)
-        } else {
-            let mon_idx = 1 + ((absolute_idx - self.config.primary_workspaces) / self.config.secondary_workspaces);
-            let rel_idx = (absolute_idx - self.config.primary_workspaces) % self.config.secondary_workspaces;
-            (mon_idx as usize, rel_idx)
-        }
+    /// Remove the matching hidden workspace with the highest index from the monitor it is on
+    fn take_hidden_workspace(&mut self, filter: impl Fn(&Workspace<B::Client>) -> bool)
+            -> Option<(MonitorConfig, Workspace<B::Client>)> {
+        let (mon_idx, rel_idx) = self.monitors.iter().enumerate()
+            .flat_map(|(mon_idx, m)| m.workspaces().enumerate()
+                .filter(|(rel_idx, ws)| *rel_idx as u32 != m.current_workspace_idx() && filter(ws))
+                .map(move |(rel_idx, ws)| (ws.global_index(), mon_idx, rel_idx as u32)))
+            .max()
+            .map(|(_, mon_idx, rel_idx)| (mon_idx, rel_idx))?;
+        let monitor = &mut self.monitors[mon_idx];
+        let config = monitor.config().clone();
+        monitor.remove_workspace(rel_idx).map(|ws| (config, ws))
     }
 
-    pub fn restart(&mut self, backend: &mut B) {
-        println!("Restarting");
-        self.cleanup(backend);
-        backend.shutdown();
+    /// Create a monitor for a newly connected output (restores the state of outputs with the same name)
+    fn attach_monitor(&mut self, config: MonitorConfig, primary: bool) -> Monitor<B::Client> {
+        let detached = self.detached_monitors.iter().position(|m| m.name() == config.name())
+            .map(|i| self.detached_monitors.remove(i));
+
+        let mut workspaces = Vec::new();
+        match detached {
+            Some(DetachedMonitor::Owned(mut monitor, clients)) => {
+                monitor.update_config(config);
+                let cur_workspace_idx = monitor.current_workspace_idx();
+                for (client_rc, rel_idx) in clients {
+                    if let Some(m) = self.get_monitor_mut(&client_rc) {
+                        m.detach_client(&client_rc);
+                    }
+                    if let Some(ws) = monitor.workspace_mut(rel_idx) {
+                        ws.attach_client(client_rc.clone());
+                        if rel_idx == cur_workspace_idx {
+                            client_rc.borrow_mut().show();
+                        } else {
+                            client_rc.borrow_mut().hide();
+                        }
+                    }
+                }
+                return monitor;
+            },
+            Some(DetachedMonitor::Shared(_, names)) => {
+                for name in names {
+                    if let Some(workspace) = self.take_hidden_workspace(|ws| ws.name() == name) {
+                        workspaces.push(workspace);
+                    }
+                }
+            },
+            None => (),
+        }
 
-        // get args without exec_path
-        let args = env::args().skip(1);
-        eprintln!("Path: {:?}", self.exec_path);
-        eprintln!("Args: {:?}", args);
+        // reuse workspaces of the pool instead of growing it
+        if self.config.shared_workspaces && workspaces.is_empty()
+            && let Some(workspace) = self.take_hidden_workspace(|_| true) {
+                workspaces.push(workspace);
+            }
 
-        let mut command = process::Command::new(self.exec_path.clone());
-        let command = command.args(args);
-        let error = command.exec();
-        eprintln!("{}", error);
-        process::exit(1);
+        if workspaces.is_empty() {
+            let workspace_offset = self.workspace_count();
+            Monitor::new(config, &self.config, primary, workspace_offset)
+        } else {
+            let workspaces = workspaces.into_iter()
+                .map(|(from_config, mut ws)| { Self::relocate_workspace(&mut ws, &from_config, &config); ws })
+                .collect::<Vec<_>>();
+            workspaces[0].clients().for_each(|c| c.borrow_mut().show());
+            Monitor::with_workspaces(config, primary, workspaces)
+        }
     }
 
-    pub fn swap_direction(&mut self, backend: &mut B, dir: Direction) {
-        if let Some(active) = self.active_client.clone() {
-            let ws = self.current_workspace_mut(backend);
-            if let Some(selected) = ws.next_in_direction(active.clone(), dir) {
-                ws.swap_clients(active, selected);
+    /// Move the clients of a disconnected monitor to the last monitor and remember where they were
+    fn detach_monitor(&mut self, mut monitor: Monitor<B::Client>) {
+        let name = monitor.config().name().to_owned();
+        self.detached_monitors.retain(|m| m.name() != name);
+        let from_config = monitor.config().clone();
+        let target_monitor = match self.monitors.last_mut() {
+            Some(target_monitor) => target_monitor,
+            None => return,
+        };
+
+        if self.config.shared_workspaces {
+            // workspaces stay in the pool
+            let cur_workspace_idx = monitor.current_workspace_idx() as usize;
+            let mut workspaces = monitor.into_workspaces();
+            let cur_workspace = workspaces.remove(cur_workspace_idx);
+            workspaces.insert(0, cur_workspace);
+
+            let names = workspaces.iter().map(|ws| ws.name().to_owned()).collect();
+            for mut workspace in workspaces {
+                Self::relocate_workspace(&mut workspace, &from_config, target_monitor.config());
+                workspace.clients().for_each(|c| c.borrow_mut().hide());
+                target_monitor.push_workspace(workspace);
             }
+            self.detached_monitors.push(DetachedMonitor::Shared(name, names));
+        } else {
+            let clients = monitor.detach_clients_by_workspace();
+            target_monitor.attach_all(clients.iter().map(|(c, _)| c.clone()).collect());
+            clients.iter().for_each(|(c, _)| c.borrow_mut().show());
+            self.detached_monitors.push(DetachedMonitor::Owned(monitor, clients));
         }
     }
 
-    pub fn switch_prev_workspace(&mut self, backend: &mut B) {
-        let prev_index = self.current_monitor(backend).prev_workspace().global_index();
-        self.switch_workspace(backend, prev_index);
+    /// Move the current workspace to another monitor (swaps the visible workspaces for shared workspaces)
+    pub fn move_workspace_to_monitor(&mut self, backend: &mut B, inc: i32) {
+        let mon_idx = self.current_monitor_index();
+        let target_idx = (mon_idx as i32 + inc).rem_euclid(self.monitors.len() as i32) as usize;
+        if target_idx == mon_idx {
+            return;
+        }
+
+        if self.config.shared_workspaces {
+            let rel_idx = self.monitors[mon_idx].current_workspace_idx();
+            let target_rel_idx = self.monitors[target_idx].current_workspace_idx();
+            self.exchange_workspaces(backend, (mon_idx, rel_idx), (target_idx, target_rel_idx));
+        } else {
+            // every monitor keeps at least one workspace
+            let monitor = &self.monitors[mon_idx];
+            if monitor.workspace_count() < 2 {
+                return;
+            }
+            let rel_idx = monitor.current_workspace_idx();
+            self.show_workspace(mon_idx, if rel_idx > 0 { rel_idx - 1 } else { 1 });
+
+            let mut workspace = match self.monitors[mon_idx].remove_workspace(rel_idx) {
+                Some(workspace) => workspace,
+                None => return,
+            };
+            let from_config = self.monitors[mon_idx].config().clone();
+            Self::relocate_workspace(&mut workspace, &from_config, self.monitors[target_idx].config());
+            self.monitors[target_idx].push_workspace(workspace);
+            self.show_workspace(target_idx, self.monitors[target_idx].workspace_count() - 1);
+            self.update_workspace_indices(backend);
+        }
+
+        // follow the workspace
+        self.select_monitor(backend, target_idx);
+        self.focus_workspace(backend, target_idx);
     }
 }
 
@@ -435,11 +1513,13 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
         self.active_client.clone()
     }
 
-    fn active_workspace(&self, backend: &mut B) -> u32 {
-        self.current_monitor(backend).current_workspace().global_index()
+    fn active_workspace(&self, _backend: &mut B) -> u32 {
+        self.current_monitor().current_workspace().global_index()
     }
 
     fn activate_client(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
+        let restored = self.get_workspace_mut(&client_rc)
+            .is_some_and(|ws| ws.restore(&client_rc));
         let monitor = self.get_monitor_mut(&client_rc).unwrap();
 
         // switch workspace
@@ -448,14 +1528,19 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
 
         if let Some(workspace_idx) = option {
             self.switch_workspace(backend, workspace_idx);
-            self.current_workspace_mut(backend).raise_client(&client_rc);
+            if restored && self.get_monitor(&client_rc)
+                    .is_some_and(|m| m.current_workspace().contains(&client_rc)) {
+                client_rc.borrow_mut().show();
+            }
+            if let Some(ws) = self.get_workspace_mut(&client_rc) {
+                ws.raise_client(&client_rc);
+            }
         } else {
             // this might be the case for pinned clients
             client_rc.borrow().raise();
         }
 
-        self.focus_client(backend, Some(client_rc.clone()));
-        client_rc.borrow().warp_pointer_to_center();
+        self.select_client(backend, client_rc);
     }
 
     fn center_client(&mut self, _backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
@@ -473,8 +1558,11 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             mon.detach_client(&client_rc)
         }
 
-        if let Some(monitor) = self.monitors.get_mut(monitor as usize) {
-            monitor.attach_client(client_rc);
+        if let Some(mon) = self.monitors.get_mut(monitor as usize) {
+            mon.attach_client(client_rc.clone());
+            if self.active_client.as_ref() == Some(&client_rc) {
+                self.cur_monitor = monitor as usize;
+            }
         } else {
             panic!("Monitor {} not found", monitor);
         }
@@ -483,7 +1571,17 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
     fn focus_client(&mut self, backend: &mut B, client_option: Option<Rc<RefCell<B::Client>>>) {
         if client_option == self.active_client {
             return;
-        } else if let Some(client_rc) = client_option {
+        }
+
+        // any other focus change (like the pointer entering a window) ends a walk through the focus history
+        if !self.focus_history_step {
+            self.end_focus_history_walk();
+        }
+
+        if let Some(client_rc) = client_option {
+            if let Some(mon_idx) = self.monitor_index(&client_rc) {
+                self.set_current_monitor(backend, mon_idx);
+            }
             self.decorate_active(client_rc.clone());
             backend.set_input_focus(client_rc.clone());
 
@@ -491,6 +1589,9 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
                 self.decorate_inactive(old_client_rc);
             }
 
+            if self.focus_history_pos.is_none() {
+                self.push_focus_history(client_rc.clone());
+            }
             self.active_client = Some(client_rc);
         } else {
             self.active_client = None;
@@ -499,9 +1600,16 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
         let ws = self.active_workspace(backend);
         backend.export_current_workspace(ws);
         backend.export_active_window(&self.active_client);
+
+        let context = match &self.active_client {
+            Some(client_rc) => self.client_hook_context(client_rc),
+            None => HookContext::new(),
+        };
+        run_hook(&self.config.hooks, HookEvent::FocusChange, context);
     }
 
     fn fullscreen_client(&mut self, _backend: &mut B, client_rc: Rc<RefCell<B::Client>>, state: bool) {
+        let was_fullscreen = client_rc.borrow().is_fullscreen();
         if let Some(mon) = self.get_monitor_mut(&client_rc) {
             if state {
                 client_rc.borrow_mut().set_fullscreen(mon.config());
@@ -512,11 +1620,22 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             if let Some(ws) = self.get_workspace(&client_rc) {
                 ws.restack();
             }
+
+            if state != was_fullscreen {
+                let event = if state { HookEvent::FullscreenEnter } else { HookEvent::FullscreenExit };
+                run_hook(&self.config.hooks, event, self.client_hook_context(&client_rc));
+            }
         }
+        self.update_title(&client_rc);
     }
 
     fn handle_button(&mut self, backend: &mut B, modifiers: u32, button: u32, target: ButtonTarget,
                      client_option: Option<Rc<RefCell<B::Client>>>) {
+        // clicking selects the monitor under the pointer
+        if let Some(mon_idx) = self.monitor_at(backend.pointer_pos()) {
+            self.set_current_monitor(backend, mon_idx);
+        }
+
         if let Some(client) = client_option.clone() {
             if let Some(ws) = self.get_workspace_mut(&client) {
                 ws.raise_client(&client);
@@ -526,24 +1645,124 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             };
         }
 
-        let actions: Vec<BindingAction> = self.button_bindings.iter().filter(|bb| bb.matches(modifiers, button, target))
-            .map(|kb| kb.action()).collect();
+        let window = client_option.as_ref().map(|c| c.borrow().id());
+        let double_click = self.register_click(button, target, window);
+        let mut actions = self.button_actions(modifiers, button, target, double_click);
+        if actions.is_empty() && target == ButtonTarget::Title {
+            // the title is part of the frame
+            actions = self.button_actions(modifiers, button, ButtonTarget::Frame, double_click);
+        }
+        if actions.iter().any(|a| !a.walks_focus_history()) {
+            self.end_focus_history_walk();
+        }
         for action in actions {
             action.execute(self, backend, client_option.clone());
         }
     }
 
+    fn handle_client_update(&mut self, _backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
+        self.redecorate(client_rc);
+    }
+
     fn handle_key(&mut self, backend: &mut B, modifiers: u32, key: u32, client_option: Option<Rc<RefCell<B::Client>>>) {
-        let actions: Vec<BindingAction> = self.key_bindings.iter().filter(|kb| kb.matches(modifiers, key))
+        if key == get_keysym("Escape") as u32 && self.switcher.as_ref().is_some_and(|s| s.is_open()) {
+            self.close_window_switcher(backend, false);
+            return;
+        }
+        self.follow_pointer_monitor(backend);
+
+        // modifiers pressed in the middle of a key sequence are part of the next key
+        if !self.key_prefix.is_empty() && is_modifier_key(key) {
+            return;
+        }
+
+        if key == get_keysym("Escape") as u32 && !self.key_prefix.is_empty() {
+            self.reset_key_prefix(backend);
+            return;
+        }
+
+        let mut pressed = self.key_prefix.clone();
+        pressed.push((modifiers, key));
+        let mode_bindings: Vec<&KeyBinding> = self.key_bindings.iter()
+            .filter(|kb| kb.mode() == self.mode.as_deref())
+            .collect();
+        let actions: Vec<BindingAction> = mode_bindings.iter().filter(|kb| kb.matches(&pressed))
             .map(|kb| kb.action()).collect();
+
+        // make sure there is always a way to leave a binding mode
+        if actions.is_empty() && self.mode.is_some() && key == get_keysym("Escape") as u32 {
+            self.exit_mode(backend);
+            return;
+        }
+
+        // wait for the next key if the pressed keys only start a sequence
+        if actions.is_empty() && let Some(binding) = mode_bindings.iter().find(|kb| kb.continues(&pressed)) {
+            if self.grabs_keyboard() || backend.grab_keyboard() {
+                let prefix: Vec<String> = binding.chords().iter().take(pressed.len())
+                    .map(|c| c.to_string()).collect();
+                backend.export_key_prefix(&prefix.join(" "));
+                if self.config.key_sequence_timeout > 0 {
+                    backend.set_timeout(Some(Duration::from_millis(self.config.key_sequence_timeout)));
+                }
+                self.key_prefix = pressed;
+            }
+            return;
+        }
+
+        // the sequence is either complete or has been aborted by an unbound key
+        self.reset_key_prefix(backend);
+        if actions.iter().any(|a| !a.walks_focus_history()) {
+            self.end_focus_history_walk();
+        }
         for action in actions {
             action.execute(self, backend, client_option.clone());
         }
     }
 
+    fn handle_key_release(&mut self, backend: &mut B, modifiers: u32, _key: u32) {
+        // the window switcher stays open as long as a modifier (apart from shift) is held
+        if modifiers & !ShiftMask == 0 {
+            self.close_window_switcher(backend, true);
+        }
+    }
+
+    fn handle_timeout(&mut self, backend: &mut B) {
+        self.reset_key_prefix(backend);
+    }
+
+    fn handle_title_button(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, index: usize, button: u32) {
+        if button != 1 {
+            return;
+        }
+
+        if let Some(action) = self.config.theming.title_buttons.get(index).map(|b| b.action()) {
+            self.end_focus_history_walk();
+            action.execute(self, backend, Some(client_rc));
+        }
+    }
+
+    fn insert_workspace(&mut self, backend: &mut B, workspace_idx: u32) {
+        let nworkspaces = self.workspace_count();
+        let (mon_idx, rel_idx) = if workspace_idx < nworkspaces {
+            self.relative_workspace_idx(workspace_idx)
+        } else {
+            // append to the last monitor
+            match self.monitors.last() {
+                Some(monitor) => (self.monitors.len() - 1, monitor.workspace_count()),
+                None => return,
+            }
+        };
+
+        let global_index = u32::min(workspace_idx, nworkspaces);
+        self.shift_workspace_indices(global_index, 1);
+        self.monitors[mon_idx].insert_workspace(&self.config, rel_idx, global_index);
+        self.update_workspace_indices(backend);
+    }
+
     fn manage(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>, workspace_preference: Option<u32>) {
+        self.follow_pointer_monitor(backend);
         let (workspace_idx, actions, initial_placement)
-                = match self.apply_window_rules(backend, client_rc.clone(), workspace_preference) {
+                = match self.apply_window_rules(client_rc.clone(), workspace_preference) {
             Some(params) => params,
             None => return,
         };
@@ -558,7 +1777,7 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
                 .and_then(|m| self.monitors.get_mut(m as usize)) {
             monitor.attach_client(client_rc.clone());
         } else {
-            self.current_monitor_mut(backend).attach_client(client_rc.clone());
+            self.current_monitor_mut().attach_client(client_rc.clone());
         }
 
 
@@ -569,11 +1788,22 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             client.set_inner_bw(self.config.theming.inner_border_width);
             client.set_outer_bw(self.config.theming.outer_border_width);
             client.set_frame_width(self.config.theming.frame_width);
+            client.set_corner_radius(self.config.theming.corner_radius);
             if self.config.theming.show_title {
                 let _ignore_result = client.add_title(&self.config.theming.font,
                                                       self.config.theming.title_hpadding,
                                                       self.config.theming.title_vpadding,
-                                                      self.config.theming.active_color);
+                                                      self.config.theming.active_color.pixel(),
+                                                      self.config.theming.title_alignment);
+            }
+            if !self.config.theming.title_buttons.is_empty() {
+                let labels: Vec<String> = self.config.theming.title_buttons.iter()
+                    .map(|b| b.label().to_owned()).collect();
+                let _ignore_result = client.add_title_buttons(&labels, &self.config.theming.font,
+                                                              self.config.theming.title_hpadding,
+                                                              self.config.theming.title_vpadding,
+                                                              self.config.theming.active_color.pixel(),
+                                                              self.config.theming.title_button_hover_color.pixel());
             }
         } else {
             client.set_inner_bw(self.config.theming.no_decoration.inner_border_width);
@@ -583,7 +1813,7 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
 
 
         // bind keys and buttons
-        for key_binding in &self.key_bindings {
+        for key_binding in self.key_bindings.iter().filter(|kb| kb.mode().is_none()) {
             client.bind_key(key_binding.modifiers(), key_binding.key());
         }
         for button_binding in &self.button_bindings {
@@ -595,8 +1825,9 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
         }
 
         drop(client);
+        self.update_title(&client_rc);
 
-        if Some(self.current_workspace(backend)) == self.get_workspace(&client_rc) {
+        if Some(self.current_workspace()) == self.get_workspace(&client_rc) {
             client_rc.borrow_mut().show();
         }
 
@@ -617,10 +1848,11 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             workspace.restack();
         }
 
+        run_hook(&self.config.hooks, HookEvent::ClientManage, self.client_hook_context(&client_rc));
+
         // set client as currently focused
         if client_rc.borrow().is_visible() {
-            self.focus_client(backend, Some(client_rc.clone()));
-            client_rc.borrow_mut().warp_pointer_to_center();
+            self.select_client(backend, client_rc.clone());
         }
 
         let clients = <marswm::MarsWM<B> as WindowManager<B, Attributes>>::clients(self).collect();
@@ -654,6 +1886,7 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             Some(workspace) => workspace,
             None => return,
         };
+        let was_minimized = from_workspace.is_minimized(&client_rc);
         from_workspace.detach_client(&client_rc);
 
         let to_workspace = match self.monitors.get_mut(mon_idx).and_then(|m| m.workspace_mut(rel_idx)) {
@@ -661,6 +1894,9 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             None => panic!("Window is detached but cannot be attached again"),
         };
         to_workspace.attach_client(client_rc.clone());
+        if was_minimized {
+            to_workspace.minimize(client_rc.clone());
+        }
 
         // adjust position to be on workspace
         Self::fix_client_to_area(client_rc.clone(), self.get_monitor_mut(&client_rc).unwrap().window_area());
@@ -670,10 +1906,84 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             client_rc.borrow_mut().hide();
         }
 
-        // TODO focus other client or drop focus
-        // hacky workaround:
-        self.active_client = None;
-        backend.export_active_window(&self.active_client);
+        // focus the previously used client instead
+        if Some(&client_rc) == self.active_client.as_ref() {
+            self.end_focus_history_walk();
+            let from_workspace_idx = self.active_workspace(backend);
+            self.focus_from_history(backend, &client_rc, Some(from_workspace_idx));
+        }
+    }
+
+    fn reload_theme(&mut self, _backend: &mut B) {
+        self.config.theming.apply_palette(&read_palette());
+
+        // switcher and snap preview are recreated with the new colors when they are used the next time
+        if !self.switcher.as_ref().is_some_and(|s| s.is_open()) {
+            self.switcher = None;
+        }
+        self.snap_preview = None;
+
+        let hover_color = self.config.theming.title_button_hover_color.pixel();
+        for client_rc in self.clients.clone() {
+            client_rc.borrow_mut().set_title_button_hover_color(hover_color);
+            self.redecorate(client_rc);
+        }
+    }
+
+    fn remove_workspace(&mut self, backend: &mut B, workspace_idx: u32) {
+        let (mon_idx, rel_idx) = self.relative_workspace_idx(workspace_idx);
+        let monitor = match self.monitors.get(mon_idx) {
+            Some(monitor) if monitor.workspace_count() > 1 => monitor,
+            _ => return,  // every monitor keeps at least one workspace
+        };
+
+        // clients are moved to the previous workspace of the monitor (or the next one when removing the first one)
+        let target_rel_idx = if rel_idx > 0 { rel_idx - 1 } else { 1 };
+        let target_idx = monitor.workspace(target_rel_idx).unwrap().global_index();
+        if monitor.current_workspace_idx() == rel_idx {
+            self.show_workspace(mon_idx, target_rel_idx);
+            if mon_idx == self.current_monitor_index() {
+                self.focus_workspace(backend, mon_idx);
+            }
+        }
+
+        let (clients, minimized): (Vec<_>, Vec<_>) = match self.monitors[mon_idx].workspace(rel_idx) {
+            Some(workspace) => (workspace.clients().cloned().collect(), workspace.minimized_clients().cloned().collect()),
+            None => return,
+        };
+        let target_visible = self.monitors[mon_idx].current_workspace().global_index() == target_idx;
+        for client_rc in clients {
+            self.move_to_workspace(backend, client_rc.clone(), target_idx);
+            if target_visible {
+                client_rc.borrow_mut().show();
+            }
+        }
+        // minimized clients stay minimized on the target workspace
+        for client_rc in minimized {
+            self.move_to_workspace(backend, client_rc, target_idx);
+        }
+
+        if self.monitors[mon_idx].remove_workspace(rel_idx).is_some() {
+            self.shift_workspace_indices(workspace_idx + 1, -1);
+            self.update_workspace_indices(backend);
+        }
+    }
+
+    fn rename_workspaces(&mut self, backend: &mut B, names: Vec<String>) {
+        let mut changed = false;
+        let mut workspaces: Vec<_> = self.monitors.iter_mut().flat_map(|m| m.workspaces_mut()).collect();
+        workspaces.sort_by_key(|ws| ws.global_index());
+        for (workspace, name) in workspaces.into_iter().zip(names) {
+            if workspace.label() != name {
+                workspace.rename(name);
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.export_workspaces(backend);
+            self.update_titles();
+        }
     }
 
     fn resize_request(&mut self, _backend: &mut B, client_rc: Rc<RefCell<B::Client>>, width: u32, height: u32) -> bool {
@@ -701,58 +2011,63 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
 
     fn set_client_pinned(&mut self, _backend: &mut B, client_rc: Rc<RefCell<B::Client>>, state: bool) {
         if let Some(ws) = self.get_workspace_mut(&client_rc) {
-            ws.set_pinned(client_rc, state);
+            ws.set_pinned(client_rc.clone(), state);
+        }
+        self.redecorate(client_rc);
+    }
+
+    fn set_workspace_count(&mut self, backend: &mut B, count: u32) {
+        let nworkspaces = self.workspace_count();
+        for _ in nworkspaces..count {
+            self.insert_workspace(backend, nworkspaces);
+        }
+        for workspace_idx in (count..nworkspaces).rev() {
+            self.remove_workspace(backend, workspace_idx);
         }
     }
 
     fn tile_client(&mut self, _backend: &mut B, client_rc: Rc<RefCell<B::Client>>, state: bool) {
         if let Some(ws) = self.get_workspace_mut(&client_rc)
             && ws.current_layout() != LayoutType::Floating {
-                ws.set_floating(client_rc, !state);
+                ws.set_floating(client_rc.clone(), !state);
             }
+        self.update_title(&client_rc);
     }
 
     fn switch_workspace(&mut self, backend: &mut B, workspace_idx: u32) {
         // abort if we are already on the specified workspace
-        if self.current_workspace(backend).global_index() == workspace_idx {
+        if self.current_workspace().global_index() == workspace_idx {
             return;
         }
 
         let (mon_idx, rel_idx) = self.relative_workspace_idx(workspace_idx);
-
-        // switch monitor if necessary
+        let cur_mon_idx = self.current_monitor_index();
         if mon_idx >= self.monitors.len() {
             return;
-        } else if mon_idx != self.current_monitor_index(backend) {
-            let (x, y) = self.monitors[mon_idx].config().dimensions().center();
-            backend.warp_pointer(x, y);
         }
 
-        // take pinned clients from old workspace and hide other clients
-        let from_workspace = self.monitors[mon_idx].current_workspace_mut();
-        let pinned_clients = from_workspace.pull_pinned();
-
-        // redecorate and hide old clients
-        for client_rc in from_workspace.clients().cloned().collect::<Vec<_>>() {
-            self.decorate_inactive(client_rc.clone());
-            client_rc.borrow_mut().hide();
+        if mon_idx == cur_mon_idx {
+            self.show_workspace(mon_idx, rel_idx);
+            self.focus_workspace(backend, mon_idx);
+        } else if self.config.shared_workspaces && self.monitors[mon_idx].current_workspace_idx() != rel_idx {
+            // pull the hidden workspace over to the current monitor
+            let cur_monitor = &self.monitors[cur_mon_idx];
+            let cur_rel_idx = cur_monitor.current_workspace_idx();
+            let slot = (0..cur_monitor.workspace_count()).find(|i| *i != cur_rel_idx)
+                .unwrap_or(cur_rel_idx);
+            self.exchange_workspaces(backend, (cur_mon_idx, slot), (mon_idx, rel_idx));
+            self.show_workspace(cur_mon_idx, slot);
+            self.focus_workspace(backend, cur_mon_idx);
+        } else if self.config.shared_workspaces && self.config.greedy_view {
+            // swap the workspace shown on the other monitor with the current one
+            let cur_rel_idx = self.monitors[cur_mon_idx].current_workspace_idx();
+            self.exchange_workspaces(backend, (cur_mon_idx, cur_rel_idx), (mon_idx, rel_idx));
+            self.focus_workspace(backend, cur_mon_idx);
+        } else {
+            self.select_monitor(backend, mon_idx);
+            self.show_workspace(mon_idx, rel_idx);
+            self.focus_workspace(backend, mon_idx);
         }
-
-        // set workspace index to new workspace
-        self.monitors[mon_idx].set_cur_workspace(rel_idx);
-
-        // attach pinned clients to new workspace and show clients
-        let to_workspace = self.monitors[mon_idx].current_workspace_mut();
-        to_workspace.push_pinned(pinned_clients);
-        to_workspace.clients().for_each(|c| c.borrow_mut().show());
-
-        // select new window to be focused
-        let new_active = if let Some(client_rc) = to_workspace.clients().find(|c| c.borrow().is_fullscreen()) {
-            Some(client_rc.clone())
-        } else { to_workspace.clients().next().cloned() };
-        self.focus_client(backend, new_active);
-
-        backend.export_current_workspace(workspace_idx);
     }
 
     fn toggle_fullscreen_client(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
@@ -766,6 +2081,8 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
     }
 
     fn unmanage(&mut self, backend: &mut B, client_rc: Rc<RefCell<B::Client>>) {
+        let hook_context = self.client_hook_context(&client_rc);
+
         // remove from clients list
         let index_option = self.clients.iter().position(|c| c == &client_rc);
         if let Some(index) = index_option {
@@ -776,16 +2093,27 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
         for mon in &mut self.monitors {
             mon.detach_client(&client_rc)
         }
+        for mon in &mut self.detached_monitors {
+            mon.forget_client(&client_rc);
+        }
 
-        // unset client as currently active
-        if Some(client_rc) == self.active_client {
+        // the entries of the window switcher might refer to this client
+        self.close_window_switcher(backend, false);
+
+        // remove from focus history and focus the previously used client instead
+        self.end_focus_history_walk();
+        self.focus_history.retain(|c| c != &client_rc);
+        if Some(&client_rc) == self.active_client.as_ref() {
             self.active_client = None;
-            backend.export_active_window(&self.active_client);
+            let workspace_idx = self.active_workspace(backend);
+            self.focus_from_history(backend, &client_rc, Some(workspace_idx));
         }
 
         let clients = <marswm::MarsWM<B> as WindowManager<B, Attributes>>::clients(self).collect();
         let clients_stacked = self.clients_stacked_order().collect();
         backend.export_client_list(clients, clients_stacked);
+
+        run_hook(&self.config.hooks, HookEvent::ClientUnmanage, hook_context);
     }
 
     fn update_monitor_config(&mut self, backend: &mut B, configs: Vec<MonitorConfig>) {
@@ -793,43 +2121,44 @@ impl<B: Backend<Attributes>> WindowManager<B, Attributes> for MarsWM<B> {
             return;
         }
 
-        let cur_monitor_count = self.monitors.len();
-
-        if configs.len() < cur_monitor_count {
-            let mut trailing_monitors = self.monitors.iter_mut().skip(configs.len() - 1);
-            let last_monitor = trailing_monitors.next().unwrap();
-            let extra_monitors = trailing_monitors;
-            for monitor in extra_monitors {
-                Monitor::transfer_all(monitor, last_monitor);
-            }
-            self.monitors.truncate(configs.len());
-        } else if configs.len() > cur_monitor_count {
-            for i in self.monitors.len()..configs.len() {
-                let primary = i == 0;
-                let workspace_offset = if primary {
-                    0
-                } else {
-                    self.config.primary_workspaces + (i - 1) as u32 * self.config.secondary_workspaces
-                };
+        let old_names: Vec<String> = self.monitors.iter().map(|m| m.config().name().to_owned()).collect();
+        let cur_monitor_name = old_names.get(self.current_monitor_index()).cloned();
 
-                let monitor = Monitor::new(configs.get(i).unwrap().clone(), &self.config, primary, workspace_offset);
+        // add monitors for new outputs
+        for (i, config) in configs.iter().enumerate() {
+            if !self.monitors.iter().any(|m| m.config().name() == config.name()) {
+                let monitor = self.attach_monitor(config.clone(), i == 0);
                 self.monitors.push(monitor);
             }
         }
 
-        for (i, monitor) in self.monitors.iter_mut().enumerate() {
-            monitor.update_config(configs.get(i).unwrap().clone());
+        // keep the order of the outputs and detach monitors of disconnected outputs
+        self.monitors.sort_by_key(|m| configs.iter().position(|c| c.name() == m.config().name()).unwrap_or(usize::MAX));
+        let nmonitors = cmp::min(configs.len(), self.monitors.len());
+        let removed_monitors: Vec<_> = self.monitors.drain(nmonitors..).collect();
+        for monitor in removed_monitors {
+            self.detach_monitor(monitor);
+        }
+        self.cur_monitor = self.monitors.iter()
+            .position(|m| Some(m.config().name()) == cur_monitor_name.as_deref())
+            .unwrap_or(0);
+
+        for (monitor, config) in self.monitors.iter_mut().zip(&configs) {
+            monitor.update_config(config.clone());
             let window_area = monitor.window_area();
             for client_rc in monitor.clients() {
                 Self::fix_client_to_area(client_rc.clone(), window_area);
             }
+        }
 
+        for name in old_names.iter().filter(|n| !configs.iter().any(|c| c.name() == n.as_str())) {
+            run_hook(&self.config.hooks, HookEvent::MonitorRemoved, HookContext::new().monitor(name));
+        }
+        for config in configs.iter().filter(|c| !old_names.iter().any(|n| n == c.name())) {
+            run_hook(&self.config.hooks, HookEvent::MonitorAdded, HookContext::new().monitor(config.name()));
         }
 
         // export desktop settings
-        let workspace_info = self.monitors.iter()
-            .flat_map(|m| m.workspaces().map(|ws| (ws.name().to_owned(), m.dimensions(), m.window_area())))
-            .collect();
-        backend.export_workspaces(workspace_info);
+        self.update_workspace_indices(backend);
     }
 }
@@ -9,19 +9,23 @@ use crate::workspace::*;
 
 pub struct Monitor<C: Client<Attributes>> {
     config: MonitorConfig,
+    primary: bool,
     workspaces: Vec<Workspace<C>>,
     cur_workspace: u32,
     prev_workspace: u32,
-    workspace_offset: u32,
 }
 
 impl<C: Client<Attributes>> Monitor<C> {
     pub fn new(monitor_config: MonitorConfig, config: &Configuration, primary: bool, workspace_offset: u32)
                 -> Monitor<C> {
 
+        let new_workspace = |name: String, i: u32| {
+            Self::create_workspace(&monitor_config, config, name, workspace_offset + i)
+        };
+
         let workspaces: Vec<Workspace<C>> = if primary {
             (0..config.primary_workspaces)
-                .map(|i| Workspace::new((i + 1).to_string(), workspace_offset + i,  monitor_config.window_area(), config.layout))
+                .map(|i| new_workspace((i + 1).to_string(), i))
                 .collect()
         } else {
             (0..config.secondary_workspaces)
@@ -31,20 +35,36 @@ impl<C: Client<Attributes>> Monitor<C> {
                     } else {
                         format!("{}:{}", monitor_config.name(), i + 1)
                     };
-                    Workspace::new(name, workspace_offset + i, monitor_config.window_area(), config.layout)
+                    new_workspace(name, i)
                 })
                 .collect()
         };
 
+        Self::with_workspaces(monitor_config, primary, workspaces)
+    }
+
+    /// Create a monitor with existing workspaces (the first one is shown)
+    pub fn with_workspaces(monitor_config: MonitorConfig, primary: bool, mut workspaces: Vec<Workspace<C>>)
+                -> Monitor<C> {
+        for ws in &mut workspaces {
+            ws.update_window_area(monitor_config.window_area());
+        }
+
         Monitor {
             config: monitor_config,
+            primary,
             workspaces,
             cur_workspace: 0,
             prev_workspace: 0,
-            workspace_offset,
         }
     }
 
+    fn create_workspace(monitor_config: &MonitorConfig, config: &Configuration, name: String, global_index: u32)
+                        -> Workspace<C> {
+        let ws_config = config.workspaces.get(global_index as usize).cloned().unwrap_or_default();
+        Workspace::new(name, global_index, monitor_config.window_area(), &config.layout, ws_config)
+    }
+
     pub fn config(&self) -> &MonitorConfig {
         &self.config
     }
@@ -57,10 +77,76 @@ impl<C: Client<Attributes>> Monitor<C> {
         &mut self.workspaces[self.cur_workspace as usize]
     }
 
+    /// Position of the current workspace in the workspaces of the monitor
+    pub fn current_workspace_idx(&self) -> u32 {
+        self.cur_workspace
+    }
+
+    /// Detach all clients and return them together with the position of the workspace they were on
+    pub fn detach_clients_by_workspace(&mut self) -> Vec<(Rc<RefCell<C>>, u32)> {
+        let mut clients = Vec::new();
+        for (i, ws) in self.workspaces.iter_mut().enumerate() {
+            clients.extend(ws.detach_all().into_iter().map(|c| (c, i as u32)));
+        }
+        clients
+    }
+
     pub fn dimensions(&self) -> Dimensions {
         self.config.dimensions()
     }
 
+    pub fn into_workspaces(self) -> Vec<Workspace<C>> {
+        self.workspaces
+    }
+
+    /// Insert a new workspace at `index` (the global indices of other workspaces are not updated)
+    pub fn insert_workspace(&mut self, config: &Configuration, index: u32, global_index: u32) {
+        let index = u32::min(index, self.workspace_count());
+
+        // use the lowest number that is not taken by another workspace for the name
+        let name = (1..).map(|i| if self.primary {
+                i.to_string()
+            } else {
+                format!("{}:{}", self.config.name(), i)
+            })
+            .find(|name| !self.workspaces.iter().any(|ws| ws.name() == name))
+            .unwrap();
+
+        let workspace = Self::create_workspace(&self.config, config, name, global_index);
+        self.workspaces.insert(index as usize, workspace);
+
+        if self.cur_workspace >= index {
+            self.cur_workspace += 1;
+        }
+        if self.prev_workspace >= index {
+            self.prev_workspace += 1;
+        }
+    }
+
+    /// Add a hidden workspace after the other workspaces of the monitor
+    pub fn push_workspace(&mut self, mut workspace: Workspace<C>) {
+        workspace.update_window_area(self.config.window_area());
+        self.workspaces.push(workspace);
+    }
+
+    /// Remove the workspace at `index` (fails if it is the current or the only workspace)
+    pub fn remove_workspace(&mut self, index: u32) -> Option<Workspace<C>> {
+        if index == self.cur_workspace || index >= self.workspace_count() {
+            return None;
+        }
+
+        let workspace = self.workspaces.remove(index as usize);
+        if self.cur_workspace > index {
+            self.cur_workspace -= 1;
+        }
+        if self.prev_workspace == index {
+            self.prev_workspace = self.cur_workspace;
+        } else if self.prev_workspace > index {
+            self.prev_workspace -= 1;
+        }
+        Some(workspace)
+    }
+
     pub fn restack_current(&self) {
         self.workspaces[self.cur_workspace as usize].restack();
     }
@@ -98,8 +184,9 @@ impl<C: Client<Attributes>> Monitor<C> {
         self.workspaces.len() as u32
     }
 
+    /// Lowest global index of the workspaces on this monitor
     pub fn workspace_offset(&self) -> u32 {
-        self.workspace_offset
+        self.workspaces.iter().map(|ws| ws.global_index()).min().unwrap_or(0)
     }
 
     pub fn workspaces(&self) -> Box<dyn Iterator<Item = &Workspace<C>> + '_>{
@@ -122,6 +209,10 @@ impl<C: Client<Attributes>> ClientList<C> for Monitor<C> {
         Box::new(self.workspaces.iter().flat_map(|ws| ws.clients()))
     }
 
+    fn contains(&self, client_rc: &Rc<RefCell<C>>) -> bool {
+        self.workspaces.iter().any(|ws| ws.contains(client_rc))
+    }
+
     fn detach_client(&mut self, client_rc: &Rc<RefCell<C>>) {
         for ws in &mut self.workspaces {
             ws.detach_client(client_rc);
@@ -134,3 +225,26 @@ impl<C: Client<Attributes>> PartialEq for Monitor<C> {
         self.config == other.config
     }
 }
+
+/// Placement of a disconnected monitor that is restored once an output with the same name reconnects
+pub enum DetachedMonitor<C: Client<Attributes>> {
+    /// monitor with the layout state of its workspaces and the positions of the workspaces its clients were on
+    Owned(Monitor<C>, Vec<(Rc<RefCell<C>>, u32)>),
+    /// names of the shared workspaces on the monitor (current workspace first)
+    Shared(String, Vec<String>),
+}
+
+impl<C: Client<Attributes>> DetachedMonitor<C> {
+    pub fn name(&self) -> &str {
+        match self {
+            DetachedMonitor::Owned(monitor, _) => monitor.config().name(),
+            DetachedMonitor::Shared(name, _) => name,
+        }
+    }
+
+    pub fn forget_client(&mut self, client_rc: &Rc<RefCell<C>>) {
+        if let DetachedMonitor::Owned(_, clients) = self {
+            clients.retain(|(c, _)| c != client_rc);
+        }
+    }
+}
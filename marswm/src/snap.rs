@@ -0,0 +1,192 @@
+use libmars::common::*;
+use libmars::common::error::*;
+use libmars::interfaces::draw::*;
+use libmars::platforms::x11::draw::canvas::X11Canvas;
+use libmars::platforms::x11::misc::atoms::X11Atom;
+use libmars::platforms::x11::misc::open_display;
+use libmars::platforms::x11::misc::window::X11Window;
+use std::mem::MaybeUninit;
+use x11::xlib;
+
+use crate::bindings::Direction;
+use crate::config::ThemingConfiguration;
+
+/// Opacity of the preview (if supported by the compositor)
+const PREVIEW_OPACITY: u64 = 0x60000000;
+/// Fractions of the window area width a window cycles through when snapped with the keyboard
+const SNAP_WIDTHS: [(u32, u32); 3] = [(1, 2), (2, 3), (1, 3)];
+
+/// Areas at the edges of a monitor that snap windows dropped there
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum SnapZone {
+    Left,
+    Right,
+    Top,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Translucent rectangle showing where a window is going to be snapped to
+///
+/// Like the window switcher the preview uses its own connection to the X server.
+pub struct SnapPreview {
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    canvas: X11Canvas,
+    outline_color: u64,
+    outline_width: u32,
+    visible: bool,
+}
+
+impl SnapZone {
+    /// Find the zone a point lies in
+    ///
+    /// * `monitor` - Dimensions of the monitor containing the point
+    /// * `threshold` - Distance in pixels to the edge of the monitor
+    pub fn at(monitor: Dimensions, point: (i32, i32), threshold: u32) -> Option<SnapZone> {
+        let threshold = threshold as i32;
+        let left = point.0 < monitor.x() + threshold;
+        let right = point.0 >= monitor.right() - threshold;
+        let top = point.1 < monitor.y() + threshold;
+        let bottom = point.1 >= monitor.bottom() - threshold;
+
+        // corners extend along the edges to make them easier to hit
+        let upper_quarter = point.1 < monitor.y() + monitor.h() as i32 / 4;
+        let lower_quarter = point.1 >= monitor.bottom() - monitor.h() as i32 / 4;
+        let left_quarter = point.0 < monitor.x() + monitor.w() as i32 / 4;
+        let right_quarter = point.0 >= monitor.right() - monitor.w() as i32 / 4;
+
+        if (left && upper_quarter) || (top && left_quarter) {
+            Some(SnapZone::TopLeft)
+        } else if (right && upper_quarter) || (top && right_quarter) {
+            Some(SnapZone::TopRight)
+        } else if (left && lower_quarter) || (bottom && left_quarter) {
+            Some(SnapZone::BottomLeft)
+        } else if (right && lower_quarter) || (bottom && right_quarter) {
+            Some(SnapZone::BottomRight)
+        } else if left {
+            Some(SnapZone::Left)
+        } else if right {
+            Some(SnapZone::Right)
+        } else if top {
+            Some(SnapZone::Top)
+        } else {
+            None
+        }
+    }
+
+    /// Dimensions a window snapped to this zone takes up
+    pub fn area(&self, window_area: Dimensions) -> Dimensions {
+        let (x, y, w, h) = window_area.as_tuple();
+        let (half_w, half_h) = (w / 2, h / 2);
+        let (center_x, center_y) = (x + half_w as i32, y + half_h as i32);
+        match self {
+            SnapZone::Left => Dimensions::new(x, y, half_w, h),
+            SnapZone::Right => Dimensions::new(center_x, y, w - half_w, h),
+            SnapZone::Top => window_area,
+            SnapZone::TopLeft => Dimensions::new(x, y, half_w, half_h),
+            SnapZone::TopRight => Dimensions::new(center_x, y, w - half_w, half_h),
+            SnapZone::BottomLeft => Dimensions::new(x, center_y, half_w, h - half_h),
+            SnapZone::BottomRight => Dimensions::new(center_x, center_y, w - half_w, h - half_h),
+        }
+    }
+}
+
+/// Next geometry when snapping a window to a side of the window area with the keyboard
+///
+/// Snapping to the same side again cycles through the widths in [SNAP_WIDTHS].
+pub fn next_snap_width(current: Dimensions, window_area: Dimensions, dir: Direction) -> Dimensions {
+    let (x, y, w, h) = window_area.as_tuple();
+    let snapped = |(num, denom): (u32, u32)| {
+        let width = w * num / denom;
+        match dir {
+            Direction::Left => Dimensions::new(x, y, width, h),
+            _ => Dimensions::new(window_area.right() - width as i32, y, width, h),
+        }
+    };
+
+    let next_index = SNAP_WIDTHS.iter()
+        .position(|fraction| snapped(*fraction) == current)
+        .map(|i| (i + 1) % SNAP_WIDTHS.len())
+        .unwrap_or(0);
+    snapped(SNAP_WIDTHS[next_index])
+}
+
+impl SnapPreview {
+    pub fn new(theming: &ThemingConfiguration) -> Result<SnapPreview> {
+        let display = open_display()?;
+        let window = unsafe {
+            let window = xlib::XCreateSimpleWindow(display, xlib::XDefaultRootWindow(display),
+                                                   0, 0, 1, 1, 0, 0, theming.active_color.pixel());
+
+            // the preview is not supposed to be managed by the window manager
+            let mut attributes: xlib::XSetWindowAttributes = MaybeUninit::zeroed().assume_init();
+            attributes.override_redirect = xlib::True;
+            xlib::XChangeWindowAttributes(display, window, xlib::CWOverrideRedirect, &mut attributes);
+            xlib::XSelectInput(display, window, xlib::ExposureMask);
+            window
+        };
+        window.x11_replace_property_long(display, X11Atom::NetWMWindowOpacity, xlib::XA_CARDINAL, &[PREVIEW_OPACITY]);
+
+        let mut canvas = X11Canvas::new_for_window(display, window)?;
+        canvas.set_foreground(theming.active_color.pixel())?;
+        canvas.set_background(theming.active_color.pixel())?;
+
+        Ok(SnapPreview {
+            display, window, canvas,
+            outline_color: theming.inactive_color.pixel(),
+            outline_width: theming.frame_width.1.max(1),
+            visible: false,
+        })
+    }
+
+    fn draw(&mut self) {
+        let (w, h) = (self.canvas.dimensions().w(), self.canvas.dimensions().h());
+        self.canvas.fill_rectangle(0, 0, w, h);
+        self.canvas.draw_rectangle_with(0, 0, w, h, self.outline_color, self.outline_width);
+        self.canvas.flush();
+    }
+
+    pub fn hide(&mut self) {
+        if self.visible {
+            unsafe {
+                xlib::XUnmapWindow(self.display, self.window);
+                xlib::XFlush(self.display);
+            }
+            self.visible = false;
+        }
+    }
+
+    pub fn show(&mut self, dimensions: Dimensions) {
+        unsafe {
+            xlib::XMoveResizeWindow(self.display, self.window, dimensions.x(), dimensions.y(),
+                                    dimensions.w(), dimensions.h());
+            if !self.visible {
+                xlib::XMapRaised(self.display, self.window);
+            }
+        }
+        self.visible = true;
+
+        self.canvas.match_resize();
+        self.draw();
+        self.process_events();
+    }
+
+    /// Redraw the preview if it has been exposed
+    fn process_events(&mut self) {
+        let mut exposed = false;
+        unsafe {
+            xlib::XSync(self.display, xlib::False);
+            while xlib::XPending(self.display) > 0 {
+                let mut event: MaybeUninit<xlib::XEvent> = MaybeUninit::uninit();
+                xlib::XNextEvent(self.display, event.as_mut_ptr());
+                exposed |= event.assume_init().get_type() == xlib::Expose;
+            }
+        }
+        if exposed {
+            self.draw();
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use libmars::common::*;
+use libmars::common::error::*;
+use libmars::interfaces::draw::*;
+use libmars::interfaces::wm::Client;
+use libmars::platforms::x11::draw::canvas::X11Canvas;
+use libmars::platforms::x11::draw::widget::*;
+use libmars::platforms::x11::misc::open_display;
+use std::cell::RefCell;
+use std::cmp;
+use std::mem::MaybeUninit;
+use std::rc::Rc;
+use x11::xlib;
+
+use crate::attributes::*;
+use crate::config::ThemingConfiguration;
+
+/// Padding between the overlay border and the list of windows
+const OVERLAY_PADDING: u32 = 5;
+
+/// Overlay listing windows to switch between
+///
+/// The overlay uses its own connection to the X server, as drawing and handling exposure events
+/// must not interfere with the event loop of the backend.
+/// The connection is kept open for the lifetime of the window manager.
+pub struct WindowSwitcher<C: Client<Attributes>> {
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    canvas: X11Canvas,
+    theming: ThemingConfiguration,
+    rows: Vec<X11FlowLayoutWidget<X11TextWidget>>,
+    clients: Vec<Rc<RefCell<C>>>,
+    selected: usize,
+}
+
+impl<C: Client<Attributes>> WindowSwitcher<C> {
+    pub fn new(theming: &ThemingConfiguration) -> Result<WindowSwitcher<C>> {
+        let display = open_display()?;
+        let window = unsafe {
+            let screen = xlib::XDefaultScreen(display);
+            let window = xlib::XCreateSimpleWindow(display, xlib::XDefaultRootWindow(display),
+                                                   0, 0, 1, 1, theming.outer_border_width,
+                                                   theming.border_color.pixel(), xlib::XBlackPixel(display, screen));
+
+            // the overlay is not supposed to be managed by the window manager
+            let mut attributes: xlib::XSetWindowAttributes = MaybeUninit::zeroed().assume_init();
+            attributes.override_redirect = xlib::True;
+            xlib::XChangeWindowAttributes(display, window, xlib::CWOverrideRedirect, &mut attributes);
+            xlib::XSelectInput(display, window, xlib::StructureNotifyMask | xlib::ExposureMask);
+            window
+        };
+
+        let mut canvas = X11Canvas::new_for_window(display, window)?;
+        canvas.set_foreground(theming.inactive_color.pixel())?;
+        canvas.set_background(theming.inactive_color.pixel())?;
+
+        Ok(WindowSwitcher {
+            display, window, canvas,
+            theming: theming.clone(),
+            rows: Vec::new(),
+            clients: Vec::new(),
+            selected: 0,
+        })
+    }
+
+    /// Close the overlay and return the selected client
+    pub fn close(&mut self) -> Option<Rc<RefCell<C>>> {
+        let selected = self.clients.get(self.selected).cloned();
+        self.rows.clear();
+        self.clients.clear();
+        unsafe {
+            xlib::XUnmapWindow(self.display, self.window);
+            xlib::XSync(self.display, xlib::False);
+        }
+        self.discard_events();
+        selected
+    }
+
+    /// Move the selection by `inc` entries (wrapping around)
+    pub fn cycle(&mut self, inc: i32) {
+        if self.clients.is_empty() {
+            return;
+        }
+
+        let len = self.clients.len() as i32;
+        let selected = (self.selected as i32 + inc).rem_euclid(len) as usize;
+        self.select(selected);
+        self.process_events();
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.clients.is_empty()
+    }
+
+    /// Show the overlay centered on `area`
+    ///
+    /// * `entries` - Clients to list together with the name of their workspace
+    pub fn open(&mut self, entries: Vec<(Rc<RefCell<C>>, String)>, area: Dimensions) {
+        if entries.is_empty() {
+            return;
+        }
+
+        self.rows.clear();
+        for (client_rc, workspace) in &entries {
+            let client = client_rc.borrow();
+            let labels = [workspace.clone(), client.application(), client.title()];
+            match self.create_row(&labels) {
+                Ok(row) => self.rows.push(row),
+                Err(e) => {
+                    eprintln!("Unable to create window switcher entry: {}", e);
+                    self.rows.clear();
+                    return;
+                },
+            }
+        }
+
+        self.clients = entries.into_iter().map(|(c, _)| c).collect();
+        self.align_columns(area.w() / 2);
+
+        // arrange rows below each other and fit the overlay around them
+        let mut width = 0;
+        let mut y = OVERLAY_PADDING as i32;
+        for row in &mut self.rows {
+            row.move_to(OVERLAY_PADDING as i32, y);
+            let (rw, rh) = row.size();
+            width = cmp::max(width, rw);
+            y += rh as i32;
+        }
+        let width = width + 2 * OVERLAY_PADDING;
+        let height = y as u32 + OVERLAY_PADDING;
+        let x = area.x() + (area.w() as i32 - width as i32) / 2;
+        let y = area.y() + (area.h() as i32 - height as i32) / 2;
+
+        unsafe {
+            xlib::XMoveResizeWindow(self.display, self.window, x, y, width, height);
+            xlib::XMapRaised(self.display, self.window);
+        }
+        self.canvas.match_resize();
+
+        self.select(if self.clients.len() > 1 { 1 } else { 0 });
+        self.process_events();
+    }
+
+    fn align_columns(&mut self, max_width: u32) {
+        let columns = self.rows.first().map(|r| r.count_children()).unwrap_or(0);
+        for i in 0..columns {
+            let column_width = self.rows.iter()
+                .filter_map(|r| r.child(i))
+                .map(|c| c.size().0)
+                .max().unwrap_or(0);
+            let column_width = cmp::min(column_width, max_width);
+            for row in &mut self.rows {
+                if let Some(child) = row.child_mut(i) {
+                    child.set_min_size((column_width, MIN_SIZE.1));
+                    child.set_max_size((column_width, MAX_SIZE.1));
+                }
+            }
+        }
+
+        for row in &mut self.rows {
+            row.rearrange();
+        }
+    }
+
+    fn create_row(&self, labels: &[String]) -> Result<X11FlowLayoutWidget<X11TextWidget>> {
+        let text_params = X11WidgetParams::new(0, 0, self.theming.title_hpadding, self.theming.title_vpadding);
+        let mut row = X11FlowLayoutWidget::new(self.display, self.window, X11WidgetParams::new(0, 0, 0, 0),
+                                               0, Vec::new(), self.theming.inactive_color.pixel())?;
+        for label in labels {
+            let widget = X11TextWidget::new(self.display, row.wid(), text_params, label.clone(),
+                                            &self.theming.font, self.theming.active_color.pixel(),
+                                            self.theming.inactive_color.pixel())?;
+            row.push(widget);
+        }
+        Ok(row)
+    }
+
+    fn discard_events(&mut self) {
+        unsafe {
+            while xlib::XPending(self.display) > 0 {
+                let mut event: MaybeUninit<xlib::XEvent> = MaybeUninit::uninit();
+                xlib::XNextEvent(self.display, event.as_mut_ptr());
+            }
+        }
+    }
+
+    /// Redraw all parts of the overlay that have been exposed
+    fn process_events(&mut self) {
+        unsafe {
+            xlib::XSync(self.display, xlib::False);
+            while xlib::XPending(self.display) > 0 {
+                let mut event: MaybeUninit<xlib::XEvent> = MaybeUninit::uninit();
+                xlib::XNextEvent(self.display, event.as_mut_ptr());
+                let event = event.assume_init();
+
+                if event.get_type() == xlib::Expose && event.expose.window == self.window {
+                    let (w, h) = (self.canvas.dimensions().w(), self.canvas.dimensions().h());
+                    self.canvas.fill_rectangle(0, 0, w, h);
+                    self.canvas.flush();
+                } else {
+                    for row in &mut self.rows {
+                        if row.handle_xevent(event) {
+                            break;
+                        }
+                    }
+                }
+            }
+            xlib::XFlush(self.display);
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            let (fg, bg) = if i == index {
+                (self.theming.inactive_color.pixel(), self.theming.active_color.pixel())
+            } else {
+                (self.theming.active_color.pixel(), self.theming.inactive_color.pixel())
+            };
+            for child in row.children_mut() {
+                let _ = child.set_foreground(fg).and(child.set_background(bg));
+            }
+        }
+    }
+}
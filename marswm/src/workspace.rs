@@ -1,35 +1,63 @@
 use libmars::common::*;
 use libmars::interfaces::wm::Client;
+use libmars::platforms::x11::misc::WINDOW_MIN_SIZE;
 use std::cell::RefCell;
 use std::cmp;
 use std::collections::VecDeque;
 use std::rc::Rc;
 
 use crate::*;
-use crate::config::LayoutConfiguration;
+use crate::bsp::BspTree;
+use crate::config::{LayoutConfiguration, WorkspaceConfiguration};
 use crate::layouts::*;
 
+/// Smallest share of the main area
+const MIN_MAIN_RATIO: f32 = 0.2;
+/// Largest share of the main area
+const MAX_MAIN_RATIO: f32 = 0.8;
+
+/// Layout state at the beginning of resizing a tiled client with the mouse
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct ResizeOrigin {
+    main_ratio: f32,
+    nmain: u32,
+    pointer: (i32, i32),
+    is_main: bool,
+}
+
 #[derive(PartialEq)]
 pub struct Workspace<C: Client<Attributes>> {
     name: String,
+    icon: Option<String>,
     global_index: u32,
     clients: VecDeque<Rc<RefCell<C>>>,  // sorted by user
     clients_stack: VecDeque<Rc<RefCell<C>>>,  // sorted by stacking order
+    minimized: Vec<Rc<RefCell<C>>>,  // most recently minimized last
     win_area: Dimensions,
     cur_layout: LayoutType,
     layout_config: LayoutConfiguration,
+    layouts: Vec<LayoutType>,
+    bsp_tree: RefCell<BspTree<C>>,
 }
 
 
 impl<C: Client<Attributes>> Workspace<C> {
-    pub fn new(name: String, global_index: u32, win_area: Dimensions, layout_config: LayoutConfiguration) -> Workspace<C> {
+    /// Create a workspace with the settings of `ws_config` (`name` is used if it doesn't contain one)
+    pub fn new(name: String, global_index: u32, win_area: Dimensions, layout_config: &LayoutConfiguration,
+               ws_config: WorkspaceConfiguration) -> Workspace<C> {
+        let layout_config = ws_config.layout_config(layout_config);
         Workspace {
-            name, global_index,
+            name: ws_config.name.unwrap_or(name),
+            icon: ws_config.icon,
+            global_index,
             clients: VecDeque::new(),
             clients_stack: VecDeque::new(),
+            minimized: Vec::new(),
             win_area,
-            cur_layout: layout_config.default,
+            cur_layout: layout_config.default.clone(),
             layout_config,
+            layouts: ws_config.layouts,
+            bsp_tree: RefCell::new(BspTree::new()),
         }
     }
 
@@ -37,28 +65,80 @@ impl<C: Client<Attributes>> Workspace<C> {
         let tiled_clients: Vec<_> = self.clients.iter()
             .filter(|c| !c.borrow().attributes().is_floating && !c.borrow().is_dialog())
             .cloned().collect();
-        Layout::get(self.cur_layout).apply_layout(self.win_area, &tiled_clients, &self.layout_config);
+        let single_tiled = tiled_clients.len() == 1 && self.cur_layout != LayoutType::Floating;
+
+        if self.layout_config.smart_borders {
+            let hide = single_tiled || self.cur_layout == LayoutType::Monocle;
+            for client_rc in &self.clients {
+                if !client_rc.borrow().is_fullscreen() {
+                    let hidden = hide && tiled_clients.contains(client_rc);
+                    client_rc.borrow_mut().set_decoration_hidden(hidden);
+                }
+            }
+        }
+
+        let mut config = self.layout_config.clone();
+        if config.smart_gaps && single_tiled {
+            config.inner_gap = 0;
+            config.outer_gap = (0, 0, 0, 0);
+        }
+
+        if self.cur_layout == LayoutType::Bsp {
+            let mut tree = self.bsp_tree.borrow_mut();
+            tree.sync(&tiled_clients, self.clients_stack.iter(), config.split_ratio);
+            tree.apply(self.win_area, config.outer_gap, config.inner_gap);
+        } else {
+            Layout::get(&self.cur_layout).apply_layout(self.win_area, &tiled_clients, &config);
+        }
+    }
+
+    /// Change the share of the client in its split (changes the main ratio for other layouts)
+    pub fn change_split_ratio(&mut self, client_rc: &Rc<RefCell<C>>, inc: f32) {
+        if self.cur_layout == LayoutType::Bsp {
+            self.bsp_tree.borrow_mut().change_ratio(client_rc, inc);
+            self.apply_layout();
+        } else {
+            self.change_main_ratio(inc);
+        }
     }
 
     pub fn change_main_ratio(&mut self, i: f32) {
         let new_ratio = self.layout_config.main_ratio + i;
-        if new_ratio > 0.20 && new_ratio < 0.80 {
+        if new_ratio > MIN_MAIN_RATIO && new_ratio < MAX_MAIN_RATIO {
             self.layout_config.main_ratio = new_ratio;
             self.apply_layout();
         }
     }
 
+    /// Change the weight of a client relative to the other clients in its stack
+    pub fn change_client_weight(&mut self, client_rc: &Rc<RefCell<C>>, inc: f32) {
+        if !self.contains(client_rc) {
+            return;
+        }
+
+        let mut client = client_rc.borrow_mut();
+        let weight = client.attributes().weight + inc;
+        client.attributes_mut().weight = weight.clamp(MIN_CLIENT_WEIGHT, MAX_CLIENT_WEIGHT);
+        drop(client);
+        self.apply_layout();
+    }
+
     pub fn clients_as_stacked(&self) -> Box<dyn Iterator<Item = &Rc<RefCell<C>>> + '_> {
         Box::new(self.clients_stack.iter())
     }
 
     pub fn current_layout(&self) -> LayoutType {
-        self.cur_layout
+        self.cur_layout.clone()
     }
 
     pub fn cycle_layout(&mut self) {
-        let cur_idx = LayoutType::VALUES.iter().position(|l| *l == self.cur_layout).unwrap();
-        self.cur_layout = LayoutType::VALUES[(cur_idx + 1) % LayoutType::SIZE];
+        let layouts = if self.layouts.is_empty() { LayoutType::VALUES } else { &self.layouts };
+
+        // continue with the first layout if the current one is not part of the cycle (e.g. external layouts)
+        let next_idx = layouts.iter().position(|l| *l == self.cur_layout)
+            .map(|i| (i + 1) % layouts.len())
+            .unwrap_or(0);
+        self.cur_layout = layouts[next_idx].clone();
         self.apply_layout();
     }
 
@@ -71,6 +151,13 @@ impl<C: Client<Attributes>> Workspace<C> {
         }
     }
 
+    pub fn flip_split(&mut self, client_rc: &Rc<RefCell<C>>) {
+        if self.cur_layout == LayoutType::Bsp {
+            self.bsp_tree.borrow_mut().flip(client_rc);
+            self.apply_layout();
+        }
+    }
+
     pub fn global_index(&self) -> u32 {
         self.global_index
     }
@@ -81,11 +168,15 @@ impl<C: Client<Attributes>> Workspace<C> {
     }
 
     pub fn inc_gaps(&mut self, i: i32) {
-        let new_gaps = self.layout_config.gap_width as i32 + i;
-        if new_gaps >= 0 && new_gaps as u32 <= self.win_area.w() / 2 && new_gaps as u32 <= self.win_area.h() / 2 {
-            self.layout_config.gap_width = new_gaps as u32;
-            self.apply_layout();
-        }
+        let max_gap = cmp::min(self.win_area.w() / 2, self.win_area.h() / 2) as i32;
+        let change = |gap: &mut u32| *gap = (*gap as i32 + i).clamp(0, max_gap) as u32;
+        let outer_gap = &mut self.layout_config.outer_gap;
+        change(&mut self.layout_config.inner_gap);
+        change(&mut outer_gap.0);
+        change(&mut outer_gap.1);
+        change(&mut outer_gap.2);
+        change(&mut outer_gap.3);
+        self.apply_layout();
     }
 
     pub fn index_of(&self, client_rc: &Rc<RefCell<C>>) -> Option<usize> {
@@ -107,6 +198,23 @@ impl<C: Client<Attributes>> Workspace<C> {
         self.clients_stack.iter().find(|c| !self.is_main(c)).cloned()
     }
 
+    pub fn is_minimized(&self, client_rc: &Rc<RefCell<C>>) -> bool {
+        self.minimized.contains(client_rc)
+    }
+
+    /// Take the client out of the layout while keeping it on the workspace
+    pub fn minimize(&mut self, client_rc: Rc<RefCell<C>>) {
+        if self.clients.contains(&client_rc) {
+            self.detach_client(&client_rc);
+            self.minimized.push(client_rc);
+        }
+    }
+
+    /// Clients that are minimized on this workspace (most recently minimized last)
+    pub fn minimized_clients(&self) -> Box<dyn Iterator<Item = &Rc<RefCell<C>>> + '_> {
+        Box::new(self.minimized.iter())
+    }
+
     pub fn move_main(&mut self, client_rc: Rc<RefCell<C>>) {
         let nmain = self.layout_config.nmain.try_into().unwrap();
         let mut index_option = None;
@@ -125,6 +233,20 @@ impl<C: Client<Attributes>> Workspace<C> {
         }
     }
 
+    /// Name of the workspace including its icon (as shown to other applications)
+    pub fn label(&self) -> String {
+        match &self.icon {
+            Some(icon) => format!("{} {}", icon, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Change the name of the workspace (also removes its icon)
+    pub fn rename(&mut self, name: String) {
+        self.name = name;
+        self.icon = None;
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -137,64 +259,13 @@ impl<C: Client<Attributes>> Workspace<C> {
         let x_diff = |c: &Rc<RefCell<C>>| c.borrow().center().0 - client_center.0;
         let y_diff = |c: &Rc<RefCell<C>>| c.borrow().center().1 - client_center.1;
 
-        let (selected, _) = match dir {
-            Up => {
-                clients.iter()
-                    .filter(|c| *c != &client_rc)
-                    .map(|c| (c, y_diff(c)))
-                    .filter(|(c, v)| v.abs() >= x_diff(c).abs())
-                    .filter(|(_, v)| *v < 0)
-                    .fold((&client_rc, i32::MIN), |(acc_c, acc_d), (cur_c, cur_d)| {
-                        if cur_d > acc_d {
-                            (cur_c, cur_d)
-                        } else {
-                            (acc_c, acc_d)
-                        }
-                    })
-            },
-            Down => {
-                clients.iter()
-                    .filter(|c| *c != &client_rc)
-                    .map(|c| (c, y_diff(c)))
-                    .filter(|(c, v)| v.abs() >= x_diff(c).abs())
-                    .filter(|(_, v)| *v > 0)
-                    .fold((&client_rc, i32::MAX), |(acc_c, acc_d), (cur_c, cur_d)| {
-                        if cur_d < acc_d {
-                            (cur_c, cur_d)
-                        } else {
-                            (acc_c, acc_d)
-                        }
-                    })
-            },
-            Left => {
-                clients.iter()
-                    .filter(|c| *c != &client_rc)
-                    .map(|c| (c, x_diff(c)))
-                    .filter(|(c, v)| v.abs() >= y_diff(c).abs())
-                    .filter(|(_, v)| *v < 0)
-                    .fold((&client_rc, i32::MIN), |(acc_c, acc_d), (cur_c, cur_d)| {
-                        if cur_d > acc_d {
-                            (cur_c, cur_d)
-                        } else {
-                            (acc_c, acc_d)
-                        }
-                    })
-            },
-            Right => {
-                clients.iter()
-                    .filter(|c| *c != &client_rc)
-                    .map(|c| (c, x_diff(c)))
-                    .filter(|(c, v)| v.abs() >= y_diff(c).abs())
-                    .filter(|(_, v)| *v > 0)
-                    .fold((&client_rc, i32::MAX), |(acc_c, acc_d), (cur_c, cur_d)| {
-                        if cur_d < acc_d {
-                            (cur_c, cur_d)
-                        } else {
-                            (acc_c, acc_d)
-                        }
-                    })
-            },
-        };
+        // the closest client in the given direction (window centers are compared for floating windows as well)
+        let selected = clients.iter()
+            .filter(|c| *c != &client_rc)
+            .filter_map(|c| dir.distance(client_center, c.borrow().center()).map(|d| (c, d)))
+            .min_by_key(|(_, d)| *d)
+            .map(|(c, _)| c)
+            .unwrap_or(&client_rc);
 
         let selected = selected.clone();
         if selected == client_rc {
@@ -216,6 +287,12 @@ impl<C: Client<Attributes>> Workspace<C> {
         None
     }
 
+    pub fn preselect(&mut self, client_rc: &Rc<RefCell<C>>, dir: Direction) {
+        if self.cur_layout == LayoutType::Bsp {
+            self.bsp_tree.borrow_mut().preselect(client_rc, dir, self.layout_config.split_ratio);
+        }
+    }
+
     pub fn pull_pinned(&mut self) -> Vec<Rc<RefCell<C>>> {
         let mut vec = Vec::new();
 
@@ -263,6 +340,134 @@ impl<C: Client<Attributes>> Workspace<C> {
         }
     }
 
+    /// Put a minimized client back into the layout
+    pub fn restore(&mut self, client_rc: &Rc<RefCell<C>>) -> bool {
+        match self.minimized.iter().position(|c| c == client_rc) {
+            Some(index) => {
+                let client_rc = self.minimized.remove(index);
+                self.attach_client(client_rc);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Most recently minimized client
+    pub fn last_minimized(&self) -> Option<Rc<RefCell<C>>> {
+        self.minimized.last().cloned()
+    }
+
+    /// Capture the layout state before resizing a tiled client with the mouse
+    pub fn resize_origin(&self, client_rc: &Rc<RefCell<C>>, pointer: (i32, i32)) -> ResizeOrigin {
+        ResizeOrigin {
+            main_ratio: self.layout_config.main_ratio,
+            nmain: self.layout_config.nmain,
+            pointer,
+            is_main: self.is_main(client_rc),
+        }
+    }
+
+    /// Resize a tiled client with the mouse
+    ///
+    /// Dragging across the border between main and stack area changes the main ratio.
+    /// Dragging along it changes the number of main clients for main clients and the weights of stack clients.
+    /// Layouts without a main area only adjust the weights.
+    pub fn resize_tiled(&mut self, client_rc: &Rc<RefCell<C>>, origin: ResizeOrigin, orig_size: (u32, u32),
+                        delta: (i32, i32)) {
+        use StackPosition::*;
+        let stack_position = match self.cur_layout {
+            LayoutType::Stack | LayoutType::Deck | LayoutType::Centered => Right,
+            LayoutType::BottomStack => Bottom,
+            LayoutType::Dynamic => self.layout_config.stack_position,
+            _ => return self.resize_in_stack(client_rc, orig_size, delta),
+        };
+
+        // main and stack area are either next to each other or below each other
+        let side_by_side = matches!(stack_position, Left | Right);
+        let (across, along, area_size, client_size) = if side_by_side {
+            (delta.0, delta.1, self.win_area.w(), orig_size.1)
+        } else {
+            (delta.1, delta.0, self.win_area.h(), orig_size.0)
+        };
+
+        if across.abs() >= along.abs() {
+            let shift = across as f32 / area_size as f32;
+            let ratio = if self.cur_layout == LayoutType::Centered {
+                // the main area grows to both sides, so its share changes twice as fast
+                let sign = if origin.pointer.0 >= self.win_area.center().0 { 1.0 } else { -1.0 };
+                let orig_share = origin.main_ratio / (2.0 - origin.main_ratio);
+                let share = orig_share + sign * 2.0 * shift;
+                2.0 * share / (1.0 + share)
+            } else if matches!(stack_position, Right | Bottom) {
+                origin.main_ratio + shift
+            } else {
+                origin.main_ratio - shift
+            };
+            self.layout_config.main_ratio = ratio.clamp(MIN_MAIN_RATIO, MAX_MAIN_RATIO);
+            self.apply_layout();
+        } else if origin.is_main {
+            let steps = along / cmp::max(client_size, 1) as i32;
+            let ntiled = self.tiled_clients().count() as i32;
+            self.layout_config.nmain = (origin.nmain as i32 + steps).clamp(0, ntiled) as u32;
+            self.apply_layout();
+        } else {
+            self.resize_in_stack(client_rc, orig_size, delta);
+        }
+    }
+
+    /// Move the border between a client and the adjacent client of the same stack by redistributing their weights
+    ///
+    /// * `orig_size` - Size of the client when resizing started
+    /// * `delta` - Distance the border has been moved since then
+    pub fn resize_in_stack(&mut self, client_rc: &Rc<RefCell<C>>, orig_size: (u32, u32), delta: (i32, i32)) {
+        let dimensions = client_rc.borrow().dimensions();
+        let others: Vec<_> = self.tiled_clients()
+            .filter(|c| *c != client_rc)
+            .map(|c| (c.clone(), c.borrow().dimensions()))
+            .collect();
+
+        // clients in the same column are stacked vertically, clients in the same row horizontally
+        let column: Vec<_> = others.iter()
+            .filter(|(_, d)| d.x() == dimensions.x() && d.w() == dimensions.w())
+            .collect();
+        let row: Vec<_> = others.iter()
+            .filter(|(_, d)| d.y() == dimensions.y() && d.h() == dimensions.h())
+            .collect();
+        let (stack, vertical) = if !column.is_empty() { (column, true) } else { (row, false) };
+        let pos = |d: &Dimensions| if vertical { d.y() } else { d.x() };
+        let size = |d: &Dimensions| if vertical { d.h() } else { d.w() };
+
+        // prefer the border after the client and fall back to the one before it
+        let after = stack.iter()
+            .filter(|(_, d)| pos(d) > pos(&dimensions))
+            .min_by_key(|(_, d)| pos(d));
+        let before = stack.iter()
+            .filter(|(_, d)| pos(d) < pos(&dimensions))
+            .max_by_key(|(_, d)| pos(d));
+        let ((neighbor_rc, neighbor_dimensions), sign) = match (after, before) {
+            (Some(neighbor), _) => (neighbor, 1),
+            (None, Some(neighbor)) => (neighbor, -1),
+            (None, None) => return,
+        };
+
+        let orig_size = if vertical { orig_size.1 } else { orig_size.0 } as i32;
+        let delta = if vertical { delta.1 } else { delta.0 };
+        let total_size = (size(&dimensions) + size(neighbor_dimensions)) as i32;
+        if total_size < 2 * WINDOW_MIN_SIZE as i32 {
+            return;
+        }
+        let new_size = (orig_size + sign * delta).clamp(WINDOW_MIN_SIZE as i32, total_size - WINDOW_MIN_SIZE as i32);
+
+        let total_weight = client_rc.borrow().attributes().weight + neighbor_rc.borrow().attributes().weight;
+        // keep both weights within their limits without changing their sum
+        let min_weight = f32::max(MIN_CLIENT_WEIGHT, total_weight - MAX_CLIENT_WEIGHT);
+        let max_weight = f32::min(MAX_CLIENT_WEIGHT, total_weight - MIN_CLIENT_WEIGHT);
+        let weight = (total_weight * new_size as f32 / total_size as f32).max(min_weight).min(max_weight);
+        client_rc.borrow_mut().attributes_mut().weight = weight;
+        neighbor_rc.borrow_mut().attributes_mut().weight = total_weight - weight;
+        self.apply_layout();
+    }
+
     pub fn restack(&self) {
         let mut fullscreen_client = None;
         for client_rc in self.clients() {
@@ -290,11 +495,31 @@ impl<C: Client<Attributes>> Workspace<C> {
         }
     }
 
+    /// Change the index of the workspace and update it for all clients
+    pub fn set_global_index(&mut self, global_index: u32) {
+        if global_index == self.global_index {
+            return;
+        }
+
+        self.global_index = global_index;
+        // pinned clients are exported as being on all workspaces anyway
+        for client_rc in self.clients.iter().chain(&self.minimized).filter(|c| !c.borrow().attributes().is_pinned) {
+            client_rc.borrow().export_workspace(global_index);
+        }
+    }
+
     pub fn set_layout(&mut self, layout: LayoutType) {
         self.cur_layout = layout;
         self.apply_layout();
     }
 
+    pub fn rotate_split(&mut self, client_rc: &Rc<RefCell<C>>) {
+        if self.cur_layout == LayoutType::Bsp {
+            self.bsp_tree.borrow_mut().rotate(client_rc);
+            self.apply_layout();
+        }
+    }
+
     pub fn set_floating(&mut self, client_rc: Rc<RefCell<C>>, state: bool) {
         if !self.contains(&client_rc) {
             return;
@@ -329,6 +554,14 @@ impl<C: Client<Attributes>> Workspace<C> {
         client_rc.borrow().export_pinned(state, Some(self.global_index))
     }
 
+    /// Move the border of the split containing the client to `point` (bsp layout)
+    pub fn set_split_position(&mut self, client_rc: &Rc<RefCell<C>>, point: (i32, i32)) {
+        if self.cur_layout == LayoutType::Bsp {
+            self.bsp_tree.borrow_mut().set_split_position(client_rc, point);
+            self.apply_layout();
+        }
+    }
+
     pub fn set_stack_mode(&mut self, mode: StackMode) {
         self.layout_config.stack_mode = mode;
         self.apply_layout();
@@ -346,7 +579,6 @@ impl<C: Client<Attributes>> Workspace<C> {
             self.clients.remove(pos);
             self.clients.insert(new_pos, client_rc.clone());
             self.apply_layout();
-            client_rc.borrow().warp_pointer_to_center();
         }
     }
 
@@ -362,8 +594,8 @@ impl<C: Client<Attributes>> Workspace<C> {
         if let Some(pos_a) = self.clients.iter().position(|c| c == &client_a)
             && let Some(pos_b) = self.clients.iter().position(|c| c == &client_b) {
                 self.clients.swap(pos_a, pos_b);
+                self.bsp_tree.borrow_mut().swap(&client_a, &client_b);
                 self.apply_layout();
-                client_a.borrow().warp_pointer_to_center();
             }
     }
 
@@ -394,7 +626,25 @@ impl<C: Client<Attributes>> ClientList<C> for Workspace<C> {
         Box::new(self.clients.iter())
     }
 
+    fn contains(&self, client_rc: &Rc<RefCell<C>>) -> bool {
+        self.clients.contains(client_rc) || self.minimized.contains(client_rc)
+    }
+
+    fn detach_all(&mut self) -> Vec<Rc<RefCell<C>>> {
+        let mut clients: Vec<_> = self.clients.drain(..).collect();
+        clients.append(&mut self.minimized);
+        self.clients_stack.clear();
+        *self.bsp_tree.borrow_mut() = BspTree::new();
+        self.restack();
+        clients
+    }
+
     fn detach_client(&mut self, client_rc: &Rc<RefCell<C>>) {
+        self.minimized.retain(|c| c != client_rc);
+
+        // the tree keeps its clients even while another layout is active
+        self.bsp_tree.borrow_mut().remove(client_rc);
+
         // detach from clients
         let index_option = self.clients.iter().position(|c| c == client_rc);
         if let Some(index) = index_option {